@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::downloader::{is_bare_file_name, RemoteFile};
+
+// Pre-downloaded files next to the installer for offline installs
+pub const BUNDLE_DIR: &str = "bundle";
+const INDEX_FILE: &str = "index.json";
+// Files are stored by SHA-1, so entries sharing a file share one copy
+const OBJECTS_DIR: &str = "objects";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleIndex {
+    // Keyed by source key (`SourceType::key`) or `url:<url>`
+    files: BTreeMap<String, BundledFile>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BundledFile {
+    file_name: String,
+    sha1: String,
+    size: u64,
+}
+
+pub struct Bundle {
+    dir: PathBuf,
+    index: BundleIndex,
+}
+
+impl Bundle {
+    // Returns None when there is no bundle in `dir`
+    pub fn open(dir: &Path) -> Result<Option<Self>> {
+        let index_path = dir.join(INDEX_FILE);
+        if !index_path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&index_path)
+            .with_context(|| format!("Failed to read {}", index_path.display()))?;
+        let index = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", index_path.display()))?;
+        Ok(Some(Self {
+            dir: dir.to_path_buf(),
+            index,
+        }))
+    }
+
+    // Opens the bundle in `dir` to add files to it, creating it if needed
+    pub fn create(dir: &Path) -> Result<Self> {
+        match Self::open(dir)? {
+            Some(bundle) => Ok(bundle),
+            None => Ok(Self {
+                dir: dir.to_path_buf(),
+                index: BundleIndex::default(),
+            }),
+        }
+    }
+
+    pub fn url_key(url: &str) -> String {
+        format!("url:{url}")
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.index.files.contains_key(key)
+    }
+
+    // The bundled copy as a local file, verified like a download when copied into place.
+    // A hash from config.yaml must match the bundled one. Entries without one, such as
+    // Maven or fingerprint-only files, are checked against the SHA-1 recorded when the
+    // bundle was created, which was verified against the source's checksums.
+    pub fn get_remote_file(&self, key: &str, expected_sha1: &str) -> Result<Option<RemoteFile>> {
+        let Some(file) = self.index.files.get(key) else {
+            return Ok(None);
+        };
+        if !is_sha1(&file.sha1) || !is_bare_file_name(&file.file_name) {
+            bail!("Bundled file {key} has an invalid entry in {INDEX_FILE}");
+        }
+        if !expected_sha1.is_empty() && !file.sha1.eq_ignore_ascii_case(expected_sha1) {
+            log::warn!("Bundled file {key} does not have the expected hash, ignoring it.");
+            return Ok(None);
+        }
+        let path = self.object_path(&file.sha1);
+        if !path.is_file() {
+            log::warn!("Bundled file {} is missing: {}", key, path.display());
+            return Ok(None);
+        }
+        Ok(Some(RemoteFile {
+            file_name: Some(file.file_name.clone()),
+            sha1: Some(file.sha1.clone()),
+            size: Some(file.size),
+            ..RemoteFile::from_local_path(&path)?
+        }))
+    }
+
+    // Moves a verified file into the bundle
+    pub fn add(
+        &mut self,
+        key: &str,
+        file_name: &str,
+        sha1: &str,
+        size: u64,
+        path: &Path,
+    ) -> Result<()> {
+        let object_path = self.object_path(sha1);
+        if object_path.exists() {
+            fs::remove_file(path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        } else {
+            let objects_dir = self.dir.join(OBJECTS_DIR);
+            fs::create_dir_all(&objects_dir)
+                .with_context(|| format!("Failed to create {}", objects_dir.display()))?;
+            fs::rename(path, &object_path).with_context(|| {
+                format!(
+                    "Failed to move {} to {}",
+                    path.display(),
+                    object_path.display()
+                )
+            })?;
+        }
+        self.index.files.insert(
+            key.to_string(),
+            BundledFile {
+                file_name: file_name.to_string(),
+                sha1: sha1.to_string(),
+                size,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let index_path = self.dir.join(INDEX_FILE);
+        let content = serde_json::to_string_pretty(&self.index)?;
+        fs::write(&index_path, content)
+            .with_context(|| format!("Failed to write {}", index_path.display()))
+    }
+
+    fn object_path(&self, sha1: &str) -> PathBuf {
+        self.dir.join(OBJECTS_DIR).join(sha1.to_ascii_lowercase())
+    }
+}
+
+fn is_sha1(hash: &str) -> bool {
+    hash.len() == 40 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
@@ -0,0 +1,181 @@
+use std::env;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+const CACHE_DIR_NAME: &str = "mm-installer";
+// Least recently used files are evicted beyond this size
+const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024 * 1024;
+// Overrides the maximum size in bytes
+const MAX_SIZE_ENV: &str = "MM_INSTALLER_CACHE_MAX_SIZE";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheUsage {
+    pub dir: String,
+    pub size: u64,
+    pub file_count: u64,
+    pub max_size: u64,
+}
+
+// Downloaded files shared by every run and instance on this machine.
+// Files are stored as `<sha1>/<file name>`.
+#[derive(Clone, Debug)]
+pub struct DownloadCache {
+    dir: PathBuf,
+    max_size: u64,
+}
+
+impl DownloadCache {
+    // Returns None when the platform has no user cache directory
+    pub fn open_default() -> Option<Self> {
+        let dir = user_cache_dir()?.join(CACHE_DIR_NAME).join("downloads");
+        let max_size = env::var(MAX_SIZE_ENV)
+            .ok()
+            .and_then(|size| size.parse().ok())
+            .unwrap_or(DEFAULT_MAX_SIZE);
+        Some(Self { dir, max_size })
+    }
+
+    // Returns the cached file for a SHA-1 and marks it as recently used
+    pub fn find(&self, sha1: &str) -> Option<PathBuf> {
+        let entry_dir = self.dir.join(sha1.to_ascii_lowercase());
+        let path = fs::read_dir(&entry_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .find(|path| path.is_file())?;
+        if let Err(e) = File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+        {
+            log::warn!("Failed to touch cached file {}: {e:?}", path.display());
+        }
+        Some(path)
+    }
+
+    // Adds a verified file, then evicts old files beyond the maximum size
+    pub fn store(&self, sha1: &str, path: &Path) -> Result<()> {
+        let file_name = path
+            .file_name()
+            .with_context(|| format!("No file name in {}", path.display()))?;
+        let entry_dir = self.dir.join(sha1.to_ascii_lowercase());
+        let cached_path = entry_dir.join(file_name);
+        if cached_path.exists() {
+            return Ok(());
+        }
+        fs::create_dir_all(&entry_dir)
+            .with_context(|| format!("Failed to create {}", entry_dir.display()))?;
+        link_or_copy(path, &cached_path)?;
+        self.evict()
+    }
+
+    pub fn remove(&self, sha1: &str) -> Result<()> {
+        let entry_dir = self.dir.join(sha1.to_ascii_lowercase());
+        if entry_dir.exists() {
+            fs::remove_dir_all(&entry_dir)
+                .with_context(|| format!("Failed to remove {}", entry_dir.display()))?;
+        }
+        Ok(())
+    }
+
+    pub fn usage(&self) -> Result<CacheUsage> {
+        let entries = self.entries()?;
+        Ok(CacheUsage {
+            dir: self.dir.to_string_lossy().to_string(),
+            size: entries.iter().map(|entry| entry.size).sum(),
+            file_count: entries.len() as u64,
+            max_size: self.max_size,
+        })
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)
+                .with_context(|| format!("Failed to remove {}", self.dir.display()))?;
+        }
+        log::info!("Cleared download cache {}", self.dir.display());
+        Ok(())
+    }
+
+    fn evict(&self) -> Result<()> {
+        let mut entries = self.entries()?;
+        let mut size: u64 = entries.iter().map(|entry| entry.size).sum();
+        entries.sort_by_key(|entry| entry.modified);
+        for entry in entries {
+            if size <= self.max_size {
+                break;
+            }
+            log::info!("Evicting cached file {}", entry.dir.display());
+            fs::remove_dir_all(&entry.dir)
+                .with_context(|| format!("Failed to remove {}", entry.dir.display()))?;
+            size -= entry.size;
+        }
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<CacheEntry>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for entry_dir in fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read {}", self.dir.display()))?
+        {
+            let entry_dir = entry_dir?.path();
+            if !entry_dir.is_dir() {
+                continue;
+            }
+            let mut entry = CacheEntry {
+                dir: entry_dir.clone(),
+                size: 0,
+                modified: SystemTime::UNIX_EPOCH,
+            };
+            for file in fs::read_dir(&entry_dir)? {
+                let metadata = file?.metadata()?;
+                entry.size += metadata.len();
+                entry.modified = entry.modified.max(metadata.modified()?);
+            }
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+struct CacheEntry {
+    dir: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+// Hard links avoid a second copy on disk; copies work across volumes
+pub fn link_or_copy(source: &Path, destination: &Path) -> Result<()> {
+    if fs::hard_link(source, destination).is_ok() {
+        return Ok(());
+    }
+    fs::copy(source, destination).with_context(|| {
+        format!(
+            "Failed to copy {} to {}",
+            source.display(),
+            destination.display()
+        )
+    })?;
+    Ok(())
+}
+
+fn user_cache_dir() -> Option<PathBuf> {
+    let non_empty = |name: &str| env::var_os(name).filter(|value| !value.is_empty());
+    if cfg!(windows) {
+        non_empty("LOCALAPPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        non_empty("HOME").map(|home| PathBuf::from(home).join("Library/Caches"))
+    } else {
+        non_empty("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| non_empty("HOME").map(|home| PathBuf::from(home).join(".cache")))
+    }
+}
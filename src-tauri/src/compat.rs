@@ -0,0 +1,461 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+
+use crate::config::ModLoader;
+use crate::jar::{MetadataFormat, ModMetadata};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Loader {
+    Neoforge,
+    Forge,
+    Fabric,
+    Quilt,
+}
+
+impl Loader {
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name.contains("neoforge") {
+            Some(Loader::Neoforge)
+        } else if name.contains("forge") {
+            Some(Loader::Forge)
+        } else if name.contains("fabric") {
+            Some(Loader::Fabric)
+        } else if name.contains("quilt") {
+            Some(Loader::Quilt)
+        } else {
+            None
+        }
+    }
+
+    pub fn modrinth_name(&self) -> &'static str {
+        match self {
+            Loader::Neoforge => "neoforge",
+            Loader::Forge => "forge",
+            Loader::Fabric => "fabric",
+            Loader::Quilt => "quilt",
+        }
+    }
+}
+
+impl Display for Loader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Loader::Neoforge => write!(f, "NeoForge"),
+            Loader::Forge => write!(f, "Forge"),
+            Loader::Fabric => write!(f, "Fabric"),
+            Loader::Quilt => write!(f, "Quilt"),
+        }
+    }
+}
+
+// Minecraft and mod loader versions the launcher profile runs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameTarget {
+    pub minecraft: Option<String>,
+    pub loader: Option<Loader>,
+    pub loader_version: Option<String>,
+}
+
+impl GameTarget {
+    // Parses launcher version IDs such as `neoforge-21.1.215`, `1.20.1-forge-47.2.0`
+    // and `fabric-loader-0.16.5-1.21.1`. The loader falls back to `modLoader.name`.
+    pub fn parse(profile_version: &str, mod_loader: &ModLoader) -> Self {
+        let version = profile_version.trim();
+        let mut target = if let Some(loader_version) = version.strip_prefix("neoforge-") {
+            GameTarget {
+                minecraft: neoforge_minecraft_version(loader_version),
+                loader: Some(Loader::Neoforge),
+                loader_version: Some(loader_version.to_string()),
+            }
+        } else if let Some((minecraft, loader_version)) = version.split_once("-forge-") {
+            GameTarget {
+                minecraft: Some(minecraft.to_string()),
+                loader: Some(Loader::Forge),
+                loader_version: Some(loader_version.to_string()),
+            }
+        } else if let Some((loader, rest)) = version
+            .strip_prefix("fabric-loader-")
+            .map(|rest| (Loader::Fabric, rest))
+            .or_else(|| {
+                version
+                    .strip_prefix("quilt-loader-")
+                    .map(|rest| (Loader::Quilt, rest))
+            })
+        {
+            let (loader_version, minecraft) = match rest.split_once('-') {
+                Some((loader_version, minecraft)) => (loader_version, Some(minecraft.to_string())),
+                None => (rest, None),
+            };
+            GameTarget {
+                minecraft,
+                loader: Some(loader),
+                loader_version: Some(loader_version.to_string()),
+            }
+        } else {
+            GameTarget {
+                minecraft: version
+                    .starts_with(|c: char| c.is_ascii_digit())
+                    .then(|| version.to_string()),
+                loader: None,
+                loader_version: None,
+            }
+        };
+        if target.loader.is_none() {
+            target.loader = Loader::from_name(&mod_loader.name);
+        }
+        target
+    }
+
+    // Checks the ranges declared in a mod jar
+    pub fn check_metadata(&self, metadata: &ModMetadata) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mod_loader = match metadata.format {
+            MetadataFormat::Neoforge => Loader::Neoforge,
+            MetadataFormat::Forge => Loader::Forge,
+            MetadataFormat::Fabric => Loader::Fabric,
+        };
+        if let Some(loader) = self.loader {
+            if !self.is_loader_supported(mod_loader) {
+                problems.push(format!(
+                    "{} is built for {mod_loader}, but the profile uses {loader}",
+                    metadata.mod_id
+                ));
+                return problems;
+            }
+        }
+        if let (Some(minecraft), Some(range)) = (&self.minecraft, &metadata.minecraft_version_range)
+        {
+            if !is_in_range(minecraft, range, metadata.format) {
+                problems.push(format!(
+                    "{} requires Minecraft {range}, but the profile uses {minecraft}",
+                    metadata.mod_id
+                ));
+            }
+        }
+        if let (Some(loader), Some(loader_version), Some(range)) = (
+            self.loader,
+            &self.loader_version,
+            &metadata.loader_version_range,
+        ) {
+            if loader == mod_loader && !is_in_range(loader_version, range, metadata.format) {
+                problems.push(format!(
+                    "{} requires {loader} {range}, but the profile uses {loader_version}",
+                    metadata.mod_id
+                ));
+            }
+        }
+        problems
+    }
+
+    // Checks the `game_versions` and `loaders` of a Modrinth version
+    pub fn check_modrinth(
+        &self,
+        name: &str,
+        game_versions: &[String],
+        loaders: &[String],
+    ) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Some(minecraft) = &self.minecraft {
+            if !game_versions.is_empty() && !game_versions.contains(minecraft) {
+                problems.push(format!(
+                    "{name} supports Minecraft {}, but the profile uses {minecraft}",
+                    game_versions.join(", ")
+                ));
+            }
+        }
+        if let Some(loader) = self.loader {
+            let is_supported = loaders.iter().any(|name| {
+                [
+                    Loader::Neoforge,
+                    Loader::Forge,
+                    Loader::Fabric,
+                    Loader::Quilt,
+                ]
+                .into_iter()
+                .any(|mod_loader| {
+                    mod_loader.modrinth_name() == name && self.is_loader_supported(mod_loader)
+                })
+            });
+            if !loaders.is_empty() && !is_supported {
+                problems.push(format!(
+                    "{name} supports {}, but the profile uses {loader}",
+                    loaders.join(", ")
+                ));
+            }
+        }
+        problems
+    }
+
+    fn is_loader_supported(&self, mod_loader: Loader) -> bool {
+        match (self.loader, mod_loader) {
+            (None, _) => true,
+            (Some(loader), mod_loader) if loader == mod_loader => true,
+            // NeoForge for 1.20.1 still loads Forge mods
+            (Some(Loader::Neoforge), Loader::Forge) => self.minecraft.as_deref() == Some("1.20.1"),
+            (Some(Loader::Quilt), Loader::Fabric) => true,
+            _ => false,
+        }
+    }
+}
+
+// NeoForge versions are `<minor>.<patch>.<build>` of Minecraft 1.x
+fn neoforge_minecraft_version(loader_version: &str) -> Option<String> {
+    let mut parts = loader_version.split('.');
+    let minor: u32 = parts.next()?.parse().ok()?;
+    let patch: u32 = parts.next()?.parse().ok()?;
+    Some(if patch == 0 {
+        format!("1.{minor}")
+    } else {
+        format!("1.{minor}.{patch}")
+    })
+}
+
+// Unparsable ranges are treated as compatible
+fn is_in_range(version: &str, range: &str, format: MetadataFormat) -> bool {
+    match format {
+        MetadataFormat::Neoforge | MetadataFormat::Forge => {
+            is_in_maven_range(version, range).unwrap_or(true)
+        }
+        MetadataFormat::Fabric => is_in_fabric_range(version, range).unwrap_or(true),
+    }
+}
+
+// Maven version ranges used by mods.toml, e.g. `[1.21,1.21.2)` or `[47,)`
+fn is_in_maven_range(version: &str, range: &str) -> Option<bool> {
+    let range = range.trim();
+    if range.is_empty() || range == "*" {
+        return Some(true);
+    }
+    // A bare version is only a recommendation
+    if !range.starts_with(['[', '(']) {
+        return Some(true);
+    }
+    let mut rest = range;
+    let mut is_matched = false;
+    while !rest.is_empty() {
+        let start_inclusive = match rest.chars().next()? {
+            '[' => true,
+            '(' => false,
+            _ => return None,
+        };
+        let end = rest.find([']', ')'])?;
+        let end_inclusive = rest[end..].starts_with(']');
+        let bounds = &rest[1..end];
+        is_matched |= match bounds.split_once(',') {
+            None => compare_versions(version, bounds.trim()) == Ordering::Equal,
+            Some((lower, upper)) => {
+                let (lower, upper) = (lower.trim(), upper.trim());
+                let is_above = lower.is_empty()
+                    || match compare_versions(version, lower) {
+                        Ordering::Greater => true,
+                        Ordering::Equal => start_inclusive,
+                        Ordering::Less => false,
+                    };
+                let is_below = upper.is_empty()
+                    || match compare_versions(version, upper) {
+                        Ordering::Less => true,
+                        Ordering::Equal => end_inclusive,
+                        Ordering::Greater => false,
+                    };
+                is_above && is_below
+            }
+        };
+        rest = rest[end + 1..].trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    }
+    Some(is_matched)
+}
+
+// Fabric version predicates, e.g. `>=1.21 <1.22`, `~1.21.1` or `1.21.x`.
+// Alternatives are joined with ` || `.
+fn is_in_fabric_range(version: &str, range: &str) -> Option<bool> {
+    let mut is_matched = false;
+    for alternative in range.split("||") {
+        let mut is_all_matched = true;
+        for predicate in alternative.split_whitespace() {
+            is_all_matched &= matches_fabric_predicate(version, predicate)?;
+        }
+        is_matched |= is_all_matched;
+    }
+    Some(is_matched)
+}
+
+fn matches_fabric_predicate(version: &str, predicate: &str) -> Option<bool> {
+    if predicate == "*" {
+        return Some(true);
+    }
+    let (operator, target) = ["<=", ">=", "<", ">", "=", "~", "^"]
+        .into_iter()
+        .find_map(|operator| {
+            predicate
+                .strip_prefix(operator)
+                .map(|target| (operator, target))
+        })
+        .unwrap_or(("", predicate));
+    let stripped = target.trim_end_matches(".x").trim_end_matches(".X");
+    // Only a bare version can be a wildcard such as `1.21.x`
+    let is_wildcard = operator.is_empty() && stripped.len() < target.len();
+    let target = stripped;
+    if target.is_empty() {
+        return None;
+    }
+    let ordering = compare_versions(version, target);
+    Some(match operator {
+        "<=" => ordering != Ordering::Greater,
+        ">=" => ordering != Ordering::Less,
+        "<" => ordering == Ordering::Less,
+        ">" => ordering == Ordering::Greater,
+        "~" => ordering != Ordering::Less && has_same_prefix(version, target, 2),
+        "^" => ordering != Ordering::Less && has_same_prefix(version, target, 1),
+        "" if is_wildcard => has_same_prefix(version, target, target.split('.').count()),
+        _ => ordering == Ordering::Equal,
+    })
+}
+
+fn has_same_prefix(version: &str, target: &str, count: usize) -> bool {
+    let version_parts: Vec<&str> = version.split(['.', '-', '+']).collect();
+    target
+        .split('.')
+        .take(count)
+        .enumerate()
+        .all(|(i, part)| version_parts.get(i).map_or(part == "0", |v| *v == part))
+}
+
+// Compares dot-separated versions numerically where possible.
+// A pre-release (`1.21-pre1`) is older than its release and build metadata is ignored.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let split = |version: &str| -> (Vec<String>, Option<String>) {
+        let version = version.split('+').next().unwrap_or_default();
+        let (release, pre_release) = match version.split_once('-') {
+            Some((release, pre_release)) => (release, Some(pre_release.to_string())),
+            None => (version, None),
+        };
+        (
+            release.split('.').map(str::to_string).collect(),
+            pre_release,
+        )
+    };
+    let (a_release, a_pre_release) = split(a);
+    let (b_release, b_pre_release) = split(b);
+    for i in 0..a_release.len().max(b_release.len()) {
+        let a_part = a_release.get(i).map_or("0", String::as_str);
+        let b_part = b_release.get(i).map_or("0", String::as_str);
+        let ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+            (Ok(a_number), Ok(b_number)) => a_number.cmp(&b_number),
+            _ => a_part.cmp(b_part),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    match (a_pre_release, b_pre_release) {
+        (None, None) => Ordering::Equal,
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(a), Some(b)) => a.cmp(&b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LoaderSource;
+
+    #[test]
+    fn compares_versions() {
+        assert_eq!(compare_versions("1.21.1", "1.21"), Ordering::Greater);
+        assert_eq!(compare_versions("1.21", "1.21.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.9", "1.10"), Ordering::Less);
+        assert_eq!(compare_versions("1.21-pre1", "1.21"), Ordering::Less);
+        assert_eq!(
+            compare_versions("0.16.5+build.1", "0.16.5"),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn matches_maven_ranges() {
+        assert_eq!(is_in_maven_range("1.21.1", "[1.21,1.21.2)"), Some(true));
+        assert_eq!(is_in_maven_range("1.21.2", "[1.21,1.21.2)"), Some(false));
+        assert_eq!(is_in_maven_range("1.21", "(1.21,1.22)"), Some(false));
+        assert_eq!(is_in_maven_range("47.2.0", "[47,)"), Some(true));
+        assert_eq!(is_in_maven_range("46.0.1", "[47,)"), Some(false));
+        assert_eq!(is_in_maven_range("1.20.1", "[1.20.1]"), Some(true));
+        assert_eq!(is_in_maven_range("1.20.2", "[1.20.1]"), Some(false));
+        assert_eq!(is_in_maven_range("1.18", "(,1.18],[1.20,)"), Some(true));
+        assert_eq!(is_in_maven_range("1.19", "(,1.18],[1.20,)"), Some(false));
+        assert_eq!(is_in_maven_range("1.21", "[1.20,)"), Some(true));
+    }
+
+    #[test]
+    fn treats_bare_and_empty_maven_ranges_as_compatible() {
+        assert_eq!(is_in_maven_range("1.19", "1.20"), Some(true));
+        assert_eq!(is_in_maven_range("1.19", ""), Some(true));
+        assert_eq!(is_in_maven_range("1.19", "*"), Some(true));
+        assert_eq!(is_in_maven_range("1.19", "[1.20"), None);
+        assert!(is_in_range("1.19", "[1.20", MetadataFormat::Forge));
+    }
+
+    #[test]
+    fn matches_fabric_comparisons() {
+        assert_eq!(is_in_fabric_range("1.21.1", ">=1.21 <1.22"), Some(true));
+        assert_eq!(is_in_fabric_range("1.22", ">=1.21 <1.22"), Some(false));
+        assert_eq!(is_in_fabric_range("1.20.1", "<=1.20.1"), Some(true));
+        assert_eq!(is_in_fabric_range("1.20.1", ">1.20.1"), Some(false));
+        assert_eq!(is_in_fabric_range("0.16.5", "*"), Some(true));
+    }
+
+    #[test]
+    fn matches_fabric_exact_versions() {
+        assert_eq!(is_in_fabric_range("1.20", "=1.20"), Some(true));
+        assert_eq!(is_in_fabric_range("1.20.5", "=1.20"), Some(false));
+        assert_eq!(is_in_fabric_range("1.20.5", "1.20"), Some(false));
+        assert_eq!(is_in_fabric_range("1.20.0", "1.20"), Some(true));
+    }
+
+    #[test]
+    fn matches_fabric_wildcards() {
+        assert_eq!(is_in_fabric_range("1.21.4", "1.21.x"), Some(true));
+        assert_eq!(is_in_fabric_range("1.21", "1.21.x"), Some(true));
+        assert_eq!(is_in_fabric_range("1.22", "1.21.x"), Some(false));
+        assert_eq!(is_in_fabric_range("1.21.4", "1.21.X"), Some(true));
+    }
+
+    #[test]
+    fn matches_fabric_tilde_and_caret() {
+        assert_eq!(is_in_fabric_range("1.21.5", "~1.21.1"), Some(true));
+        assert_eq!(is_in_fabric_range("1.21", "~1.21.1"), Some(false));
+        assert_eq!(is_in_fabric_range("1.22", "~1.21.1"), Some(false));
+        assert_eq!(is_in_fabric_range("1.4.0", "^1.2.0"), Some(true));
+        assert_eq!(is_in_fabric_range("2.0.0", "^1.2.0"), Some(false));
+    }
+
+    #[test]
+    fn matches_fabric_alternatives() {
+        let range = "1.20.1 || >=1.21";
+        assert_eq!(is_in_fabric_range("1.20.1", range), Some(true));
+        assert_eq!(is_in_fabric_range("1.21.4", range), Some(true));
+        assert_eq!(is_in_fabric_range("1.20.4", range), Some(false));
+        assert_eq!(is_in_fabric_range("1.21", ">="), None);
+        assert!(is_in_range("1.21", ">=", MetadataFormat::Fabric));
+    }
+
+    #[test]
+    fn parses_profile_versions() {
+        let mod_loader = ModLoader {
+            name: "neoforge".to_string(),
+            source: LoaderSource::Direct { url: String::new() },
+            hash: String::new(),
+            auto_open: false,
+        };
+        let target = GameTarget::parse("neoforge-21.1.215", &mod_loader);
+        assert_eq!(target.minecraft.as_deref(), Some("1.21.1"));
+        assert_eq!(target.loader, Some(Loader::Neoforge));
+        let target = GameTarget::parse("1.20.1-forge-47.2.0", &mod_loader);
+        assert_eq!(target.minecraft.as_deref(), Some("1.20.1"));
+        assert_eq!(target.loader, Some(Loader::Forge));
+        let target = GameTarget::parse("fabric-loader-0.16.5-1.21.1", &mod_loader);
+        assert_eq!(target.minecraft.as_deref(), Some("1.21.1"));
+        assert_eq!(target.loader_version.as_deref(), Some("0.16.5"));
+    }
+}
@@ -1,16 +1,35 @@
 use std::{
-    collections::HashMap,
-    fs,
-    path::{Component, Path},
+    collections::{BTreeMap, HashMap, HashSet},
+    env, fs,
+    path::{Component, Path, PathBuf},
 };
 
 use anyhow::{bail, Context, Result};
 use semver::Version;
 use serde::{Deserialize, Serialize};
 
+use crate::curseforge::CurseForge;
+use crate::downloader::RemoteFile;
+use crate::github::Github;
+use crate::maven::{Maven, MavenArtifact};
 use crate::modrinth::Modrinth;
+use crate::network::HostAllowlist;
+use crate::signature;
 
-pub const LATEST_SCHEMA_VERSION: u32 = 3;
+pub const LATEST_SCHEMA_VERSION: u32 = 4;
+// Bundled content next to the installer, searched after the config directory
+const PAYLOAD_DIR: &str = "payload";
+
+pub type OptionSelections = BTreeMap<String, bool>;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Selection {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    #[serde(default, skip_serializing_if = "OptionSelections::is_empty")]
+    pub options: OptionSelections,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -20,27 +39,56 @@ pub struct ModPackConfig {
     profile: Profile,
     mod_loader: ModLoader,
     #[serde(default)]
+    variants: Vec<Variant>,
+    #[serde(default)]
+    option_groups: Vec<OptionGroup>,
+    #[serde(default)]
     mods: Vec<ModEntry>,
     #[serde(default)]
     resources: Vec<ResourceEntry>,
+    // Hosts allowed in addition to the built-in ones, e.g. a self-hosted file server
+    #[serde(default)]
+    allowed_hosts: Vec<String>,
+    // Where the newest config.yaml of the pack is published
+    update_url: Option<String>,
+    // Alternative update URLs, e.g. for beta testers
+    #[serde(default)]
+    channels: Vec<Channel>,
 
     #[serde(skip)]
     mod_index: HashMap<String, usize>,
+    #[serde(skip)]
+    resource_index: HashMap<(String, String), usize>,
 }
 
 impl ModPackConfig {
     pub fn load_from_path(path: &Path) -> Result<Self> {
         let raw = fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file at {}", path.display()))?;
+        signature::verify_file(path, raw.as_bytes())?;
+        Self::load_from_str(&raw)
+    }
+
+    // Callers are responsible for verifying the signature of `raw`
+    pub fn load_from_str(raw: &str) -> Result<Self> {
         let mut config: ModPackConfig =
-            serde_yaml::from_str(&raw).context("Failed to parse config.yaml")?;
+            serde_yaml::from_str(raw).context("Failed to parse config.yaml")?;
         config.validate()?;
         // Build indexes
         debug_assert!(config.mod_index.is_empty());
         for (i, mod_entry) in config.mods.iter().enumerate() {
-            let key = Self::mod_key(&mod_entry.source);
+            let key = mod_entry.source.key();
             config.mod_index.insert(key, i);
         }
+        debug_assert!(config.resource_index.is_empty());
+        for (i, resource_entry) in config.resources.iter().enumerate() {
+            let key = (
+                resource_entry.source.key(),
+                resource_entry.target_dir.clone(),
+            );
+            config.resource_index.insert(key, i);
+        }
+        config.assign_variants();
         Ok(config)
     }
 
@@ -48,26 +96,170 @@ impl ModPackConfig {
         &self.pack_version
     }
 
-    pub fn get_profile(&self) -> &Profile {
-        &self.profile
+    pub fn get_variant_profile(&self, variant_id: Option<&str>) -> Profile {
+        let mut profile = self.profile.clone();
+        let Some(variant) = variant_id.and_then(|id| self.get_variant(id)) else {
+            return profile;
+        };
+        if let Some(icon) = &variant.profile.icon {
+            profile.icon = icon.clone();
+        }
+        if let Some(version) = &variant.profile.version {
+            profile.version = version.clone();
+        }
+        if let Some(jvm_args) = &variant.profile.jvm_args {
+            profile.jvm_args = Some(jvm_args.clone());
+        }
+        profile
+    }
+
+    pub fn get_channels(&self) -> &Vec<Channel> {
+        &self.channels
+    }
+
+    // Falls back to updateUrl, then the first channel, for unknown or unset channels
+    pub fn get_update_url(&self, channel: Option<&str>) -> Option<&str> {
+        if let Some(channel) = channel {
+            match self.channels.iter().find(|c| c.id == channel) {
+                Some(channel) => return Some(&channel.update_url),
+                None => log::warn!("Unknown channel '{channel}', using the default one"),
+            }
+        }
+        self.update_url
+            .as_deref()
+            .or_else(|| self.channels.first().map(|c| c.update_url.as_str()))
+    }
+
+    pub fn get_allowed_hosts(&self) -> HostAllowlist {
+        HostAllowlist::new(&self.allowed_hosts)
     }
 
     pub fn get_mod_loader(&self) -> &ModLoader {
         &self.mod_loader
     }
 
-    pub fn has_mod(&self, source: &SourceType) -> bool {
-        self.mod_index.contains_key(&Self::mod_key(source))
+    pub fn get_mod(&self, source: &SourceType) -> Option<&ModEntry> {
+        self.mod_index
+            .get(&source.key())
+            .map(|&index| &self.mods[index])
     }
 
     pub fn get_mods(&self) -> &Vec<ModEntry> {
         &self.mods
     }
 
+    pub fn get_resource(&self, source: &SourceType, target_dir: &str) -> Option<&ResourceEntry> {
+        self.resource_index
+            .get(&(source.key(), target_dir.to_string()))
+            .map(|&index| &self.resources[index])
+    }
+
     pub fn get_resources(&self) -> &Vec<ResourceEntry> {
         &self.resources
     }
 
+    pub fn get_option_groups(&self) -> &Vec<OptionGroup> {
+        &self.option_groups
+    }
+
+    pub fn get_variants(&self) -> &Vec<Variant> {
+        &self.variants
+    }
+
+    pub fn get_variant(&self, id: &str) -> Option<&Variant> {
+        self.variants.iter().find(|variant| variant.id == id)
+    }
+
+    fn get_default_variant(&self) -> Option<&Variant> {
+        self.variants
+            .iter()
+            .find(|variant| variant.default)
+            .or_else(|| self.variants.first())
+    }
+
+    pub fn has_choices(&self) -> bool {
+        !self.variants.is_empty()
+            || self.mods.iter().any(|entry| entry.optional)
+            || self.resources.iter().any(|entry| entry.optional)
+    }
+
+    // Falls back to the default variant when the selected one no longer exists
+    pub fn resolve_selection(&self, selection: &Selection) -> Result<Selection> {
+        let variant = match &selection.variant {
+            Some(id) if self.get_variant(id).is_some() => Some(id.clone()),
+            Some(id) => {
+                log::warn!("Variant '{id}' is not defined, using the default variant.");
+                self.get_default_variant().map(|variant| variant.id.clone())
+            }
+            None => self.get_default_variant().map(|variant| variant.id.clone()),
+        };
+        Ok(Selection {
+            variant,
+            options: self.resolve_options(&selection.options)?,
+        })
+    }
+
+    // Fills in defaults for every optional entry and drops unknown names
+    fn resolve_options(&self, selections: &OptionSelections) -> Result<OptionSelections> {
+        let mut resolved = OptionSelections::new();
+        let optional_entries = self
+            .mods
+            .iter()
+            .filter(|entry| entry.optional)
+            .map(|entry| (&entry.name, entry.default))
+            .chain(
+                self.resources
+                    .iter()
+                    .filter(|entry| entry.optional)
+                    .map(|entry| (&entry.name, entry.default)),
+            );
+        for (name, default) in optional_entries {
+            let is_selected = selections.get(name).copied().unwrap_or(default);
+            resolved.insert(name.clone(), is_selected);
+        }
+        // Exactly one member of a pickOne group is selected. When the player
+        // selected none, the group's default member (or its first) is used.
+        for group in self.option_groups.iter().filter(|group| group.pick_one) {
+            let members: Vec<(&String, bool)> = self.get_option_group_members(&group.id).collect();
+            let selected_count = members
+                .iter()
+                .filter(|(name, _)| resolved.get(*name).copied().unwrap_or(false))
+                .count();
+            if selected_count > 1 {
+                bail!(
+                    "Only one option can be selected in group '{}' ({selected_count} selected)",
+                    group.name
+                );
+            }
+            if selected_count == 0 {
+                let fallback = members
+                    .iter()
+                    .find(|(_, default)| *default)
+                    .or(members.first());
+                if let Some((name, _)) = fallback {
+                    resolved.insert((*name).clone(), true);
+                }
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn get_option_group_members<'a>(
+        &'a self,
+        group_id: &'a str,
+    ) -> impl Iterator<Item = (&'a String, bool)> {
+        self.mods
+            .iter()
+            .filter(move |entry| entry.group.as_deref() == Some(group_id))
+            .map(|entry| (&entry.name, entry.default))
+            .chain(
+                self.resources
+                    .iter()
+                    .filter(move |entry| entry.group.as_deref() == Some(group_id))
+                    .map(|entry| (&entry.name, entry.default)),
+            )
+    }
+
     fn validate(&mut self) -> Result<()> {
         if self.schema_version > LATEST_SCHEMA_VERSION {
             bail!(
@@ -77,33 +269,135 @@ impl ModPackConfig {
             );
         }
         self.profile.validate()?;
-        self.mod_loader.validate()?;
+        for host in &self.allowed_hosts {
+            let host = host.trim();
+            if host.is_empty() || host.contains(['/', ':', '*']) {
+                bail!("allowedHosts must contain host names only, got '{host}'");
+            }
+        }
+        let allowlist = self.get_allowed_hosts();
+        if let Some(update_url) = &self.update_url {
+            allowlist
+                .check_str(update_url)
+                .context("updateUrl is not allowed")?;
+        }
+        let mut channel_ids = HashSet::new();
+        for channel in &self.channels {
+            if channel.id.trim().is_empty() {
+                bail!("channels.id must not be empty");
+            }
+            if !channel_ids.insert(channel.id.as_str()) {
+                bail!("channels.id '{}' is duplicated", channel.id);
+            }
+            allowlist
+                .check_str(&channel.update_url)
+                .with_context(|| format!("updateUrl of channel '{}' is not allowed", channel.id))?;
+        }
+        self.mod_loader.validate(&allowlist)?;
         for entry in self.mods.iter_mut() {
-            entry.validate()?;
+            entry.validate(&allowlist)?;
         }
         for entry in self.resources.iter_mut() {
-            entry.validate()?;
+            entry.validate(&allowlist)?;
         }
+        self.validate_options()?;
+        self.validate_variants()?;
 
         Ok(())
     }
 
-    fn mod_key(source: &SourceType) -> String {
-        match source {
-            SourceType::Curseforge {
-                project_id,
-                file_id,
-            } => format!("cf:{project_id}:{file_id}"),
-            SourceType::Modrinth {
-                project_id,
-                version_id,
-            } => format!("mr:{project_id}:{version_id}"),
-            SourceType::Direct { url } => format!("direct:{url}"),
+    fn validate_variants(&self) -> Result<()> {
+        let mut variant_ids = HashSet::new();
+        for variant in &self.variants {
+            if !variant_ids.insert(variant.id.as_str()) {
+                bail!("variants.id '{}' is duplicated", variant.id);
+            }
+            for name in variant.include.iter().chain(variant.exclude.iter()) {
+                let is_known = self.mods.iter().any(|entry| &entry.name == name)
+                    || self.resources.iter().any(|entry| &entry.name == name);
+                if !is_known {
+                    bail!("Variant '{}' refers to unknown entry '{name}'", variant.id);
+                }
+            }
+        }
+        if self
+            .variants
+            .iter()
+            .filter(|variant| variant.default)
+            .count()
+            > 1
+        {
+            bail!("Only one variant can be the default");
         }
+        Ok(())
+    }
+
+    // Entries named in some variant's include list belong only to those variants,
+    // other entries belong to every variant not excluding them
+    fn assign_variants(&mut self) {
+        if self.variants.is_empty() {
+            return;
+        }
+        let variants = &self.variants;
+        let get_members = |name: &str| -> Vec<String> {
+            let is_included_somewhere = variants
+                .iter()
+                .any(|variant| variant.include.iter().any(|n| n == name));
+            variants
+                .iter()
+                .filter(|variant| {
+                    !is_included_somewhere || variant.include.iter().any(|n| n == name)
+                })
+                .filter(|variant| !variant.exclude.iter().any(|n| n == name))
+                .map(|variant| variant.id.clone())
+                .collect()
+        };
+        for entry in self.mods.iter_mut() {
+            entry.variants = Some(get_members(&entry.name));
+        }
+        for entry in self.resources.iter_mut() {
+            entry.variants = Some(get_members(&entry.name));
+        }
+    }
+
+    fn validate_options(&self) -> Result<()> {
+        let mut group_ids = HashSet::new();
+        for group in &self.option_groups {
+            if !group_ids.insert(group.id.as_str()) {
+                bail!("optionGroups.id '{}' is duplicated", group.id);
+            }
+        }
+        let mut option_names = HashSet::new();
+        let entries = self
+            .mods
+            .iter()
+            .map(|entry| (&entry.name, entry.optional, &entry.group))
+            .chain(
+                self.resources
+                    .iter()
+                    .map(|entry| (&entry.name, entry.optional, &entry.group)),
+            );
+        for (name, optional, group) in entries {
+            if let Some(group) = group {
+                if !optional {
+                    bail!("'{name}' must be optional to belong to group '{group}'");
+                }
+                if !group_ids.contains(group.as_str()) {
+                    bail!("'{name}' refers to unknown option group '{group}'");
+                }
+            }
+            if optional && !option_names.insert(name.as_str()) {
+                bail!("Optional entry name '{name}' is duplicated");
+            }
+        }
+        // Defaults must satisfy the same rules as player selections
+        self.resolve_options(&OptionSelections::new())
+            .context("Invalid default options")?;
+        Ok(())
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Profile {
     pub name: String,
@@ -129,15 +423,64 @@ impl Profile {
 #[serde(rename_all = "camelCase")]
 pub struct ModLoader {
     pub name: String,
-    pub url: String,
+    #[serde(flatten)]
+    pub source: LoaderSource,
+    // Optional for Maven sources, whose checksum files are used instead
+    #[serde(default)]
     pub hash: String,
     #[serde(default)]
     pub auto_open: bool,
 }
 
 impl ModLoader {
-    fn validate(&self) -> Result<()> {
-        Ok(())
+    fn validate(&self, allowlist: &HostAllowlist) -> Result<()> {
+        match &self.source {
+            LoaderSource::Maven {
+                repository,
+                coordinates,
+            } => validate_maven(&self.name, repository, coordinates, allowlist),
+            LoaderSource::Direct { .. } if self.hash.trim().is_empty() => {
+                bail!("modLoader.hash must not be empty")
+            }
+            LoaderSource::Direct { url } => allowlist
+                .check_str(url)
+                .context("modLoader.url is not allowed"),
+        }
+    }
+}
+
+// The mod loader installer is given either by URL or by Maven coordinates
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum LoaderSource {
+    Maven {
+        repository: String,
+        coordinates: String,
+    },
+    Direct {
+        url: String,
+    },
+}
+
+impl LoaderSource {
+    pub fn get_url(&self) -> Result<String> {
+        match self {
+            LoaderSource::Maven {
+                repository,
+                coordinates,
+            } => Ok(MavenArtifact::parse(coordinates)?.url(repository)),
+            LoaderSource::Direct { url } => Ok(url.clone()),
+        }
+    }
+
+    pub async fn get_remote_file(&self, allowlist: &HostAllowlist) -> Result<RemoteFile> {
+        match self {
+            LoaderSource::Maven {
+                repository,
+                coordinates,
+            } => Maven::get_remote_file(repository, coordinates, allowlist).await,
+            LoaderSource::Direct { url } => Ok(RemoteFile::from_url(url)),
+        }
     }
 }
 
@@ -147,21 +490,54 @@ pub struct ModEntry {
     pub name: String,
     #[serde(flatten)]
     pub source: SourceType,
+    #[serde(default)]
     pub hash: String,
+    // CurseForge murmur2 fingerprint, an alternative or extra to `hash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<u32>,
     pub side: Side,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default)]
+    pub default: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<Vec<Os>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<Vec<Arch>>,
+
+    #[serde(skip)]
+    pub variants: Option<Vec<String>>,
 }
 
 impl ModEntry {
-    fn validate(&self) -> Result<()> {
-        Ok(())
+    fn validate(&self, allowlist: &HostAllowlist) -> Result<()> {
+        self.source.validate(&self.name, allowlist)?;
+        validate_checksum(&self.name, &self.source, &self.hash, self.fingerprint)
     }
 
-    pub fn should_install(&self, side: &Side) -> bool {
+    pub fn is_for_side(&self, side: &Side) -> bool {
         match &self.side {
             Side::Both => true,
             _ => &self.side == side,
         }
     }
+
+    pub fn is_for_platform(&self) -> bool {
+        is_platform_matched(self.os.as_deref(), self.arch.as_deref())
+    }
+
+    pub fn is_in_variant(&self, variant_id: Option<&str>) -> bool {
+        is_variant_member(self.variants.as_ref(), variant_id)
+    }
+
+    pub fn should_install(&self, side: &Side, selection: &Selection) -> bool {
+        self.is_for_side(side)
+            && self.is_for_platform()
+            && self.is_in_variant(selection.variant.as_deref())
+            && is_option_selected(&self.name, self.optional, self.default, &selection.options)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -170,25 +546,110 @@ pub struct ResourceEntry {
     pub name: String,
     #[serde(flatten)]
     pub source: SourceType,
+    #[serde(default)]
     pub hash: String,
+    // CurseForge murmur2 fingerprint, an alternative or extra to `hash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<u32>,
     pub target_dir: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub world_target_dir: Option<String>,
     #[serde(default)]
     pub decompress: bool,
     pub side: Side,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default)]
+    pub default: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<Vec<Os>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<Vec<Arch>>,
+
+    #[serde(skip)]
+    pub variants: Option<Vec<String>>,
 }
 
 impl ResourceEntry {
-    fn validate(&self) -> Result<()> {
+    fn validate(&self, allowlist: &HostAllowlist) -> Result<()> {
+        self.source.validate(&self.name, allowlist)?;
+        validate_checksum(&self.name, &self.source, &self.hash, self.fingerprint)?;
         validate_relative_dir(&self.target_dir, "resources.targetDir")?;
+        if let Some(world_target_dir) = &self.world_target_dir {
+            validate_relative_dir(world_target_dir, "resources.worldTargetDir")?;
+            if self.decompress {
+                bail!("resources.worldTargetDir cannot be combined with decompress");
+            }
+        }
         Ok(())
     }
 
-    pub fn should_install(&self, side: &Side) -> bool {
+    pub fn is_for_side(&self, side: &Side) -> bool {
         match &self.side {
             Side::Both => true,
             _ => &self.side == side,
         }
     }
+
+    pub fn is_for_platform(&self) -> bool {
+        is_platform_matched(self.os.as_deref(), self.arch.as_deref())
+    }
+
+    pub fn is_in_variant(&self, variant_id: Option<&str>) -> bool {
+        is_variant_member(self.variants.as_ref(), variant_id)
+    }
+
+    pub fn should_install(&self, side: &Side, selection: &Selection) -> bool {
+        self.is_for_side(side)
+            && self.is_for_platform()
+            && self.is_in_variant(selection.variant.as_deref())
+            && is_option_selected(&self.name, self.optional, self.default, &selection.options)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Channel {
+    pub id: String,
+    pub name: String,
+    pub update_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Variant {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub default: bool,
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub profile: ProfileOverride,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jvm_args: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionGroup {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub pick_one: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -206,30 +667,112 @@ pub enum SourceType {
         project_id: String,
         version_id: String,
     },
+    Github {
+        // `<owner>/<name>`
+        repo: String,
+        tag: String,
+        asset: String,
+    },
+    Maven {
+        repository: String,
+        // `group:artifact:version[:classifier][@extension]`
+        coordinates: String,
+    },
+    // Relative to the config directory or its payload/ folder
+    Local {
+        path: String,
+    },
     Direct {
         url: String,
     },
 }
 
 impl SourceType {
-    pub async fn get_download_url(&self) -> Result<String> {
+    // Identifies the file a source points to, e.g. in bundles and duplicate checks
+    pub fn key(&self) -> String {
+        match self {
+            SourceType::Curseforge {
+                project_id,
+                file_id,
+            } => format!("cf:{project_id}:{file_id}"),
+            SourceType::Modrinth {
+                project_id,
+                version_id,
+            } => format!("mr:{project_id}:{version_id}"),
+            SourceType::Github { repo, tag, asset } => format!("gh:{repo}:{tag}:{asset}"),
+            SourceType::Maven { coordinates, .. } => format!("maven:{coordinates}"),
+            SourceType::Local { path } => format!("local:{path}"),
+            SourceType::Direct { url } => format!("direct:{url}"),
+        }
+    }
+
+    pub async fn get_remote_file(
+        &self,
+        curseforge: &CurseForge,
+        allowlist: &HostAllowlist,
+        config_dir: &Path,
+    ) -> Result<RemoteFile> {
         match self {
             SourceType::Curseforge {
                 project_id,
                 file_id,
-            } => Ok(format!(
-                "https://www.curseforge.com/api/v1/mods/{project_id}/files/{file_id}/download"
-            )),
+            } => curseforge.get_remote_file(*project_id, *file_id).await,
             SourceType::Modrinth {
                 project_id,
                 version_id,
-            } => Modrinth::get_download_url(project_id, version_id).await,
-            SourceType::Direct { url } => Ok(url.clone()),
+            } => Modrinth::get_remote_file(project_id, version_id).await,
+            SourceType::Github { repo, tag, asset } => {
+                Github::get_remote_file(repo, tag, asset, allowlist).await
+            }
+            SourceType::Maven {
+                repository,
+                coordinates,
+            } => Maven::get_remote_file(repository, coordinates, allowlist).await,
+            SourceType::Local { path } => {
+                RemoteFile::from_local_path(&resolve_local_path(config_dir, path)?)
+            }
+            SourceType::Direct { url } => Ok(RemoteFile::from_url(url)),
+        }
+    }
+
+    fn validate(&self, name: &str, allowlist: &HostAllowlist) -> Result<()> {
+        if let SourceType::Direct { url } = self {
+            allowlist
+                .check_str(url)
+                .with_context(|| format!("URL of '{name}' is not allowed"))?;
+        }
+        if let SourceType::Local { path } = self {
+            if path.trim().is_empty() {
+                bail!("'{name}' must have a path");
+            }
+            validate_relative_dir(path, &format!("path of '{name}'"))?;
+        }
+        if let SourceType::Maven {
+            repository,
+            coordinates,
+        } = self
+        {
+            validate_maven(name, repository, coordinates, allowlist)?;
+        }
+        if let SourceType::Github { repo, tag, asset } = self {
+            let is_repo_valid = repo.split_once('/').is_some_and(|(owner, repo)| {
+                !owner.is_empty() && !repo.is_empty() && !repo.contains('/')
+            });
+            if !is_repo_valid {
+                bail!("'{name}' must have a repo in the form of '<owner>/<name>'");
+            }
+            if tag.trim().is_empty() {
+                bail!("'{name}' must have a tag");
+            }
+            if asset.trim().is_empty() || asset.contains(['/', '\\']) {
+                bail!("'{name}' must have an asset file name");
+            }
         }
+        Ok(())
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Side {
     Both,
@@ -237,6 +780,119 @@ pub enum Side {
     Server,
 }
 
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Os {
+    Windows,
+    Macos,
+    Linux,
+}
+
+impl Os {
+    pub fn current() -> Option<Self> {
+        match env::consts::OS {
+            "windows" => Some(Os::Windows),
+            "macos" => Some(Os::Macos),
+            "linux" => Some(Os::Linux),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Arch {
+    X86,
+    #[serde(rename = "x86_64")]
+    X86_64,
+    Aarch64,
+}
+
+impl Arch {
+    pub fn current() -> Option<Self> {
+        match env::consts::ARCH {
+            "x86" => Some(Arch::X86),
+            "x86_64" => Some(Arch::X86_64),
+            "aarch64" => Some(Arch::Aarch64),
+            _ => None,
+        }
+    }
+}
+
+fn is_platform_matched(os: Option<&[Os]>, arch: Option<&[Arch]>) -> bool {
+    let is_os_matched = os.map_or(true, |os| Os::current().is_some_and(|c| os.contains(&c)));
+    let is_arch_matched = arch.map_or(true, |arch| {
+        Arch::current().is_some_and(|c| arch.contains(&c))
+    });
+    is_os_matched && is_arch_matched
+}
+
+fn is_variant_member(variants: Option<&Vec<String>>, variant_id: Option<&str>) -> bool {
+    match (variants, variant_id) {
+        (Some(variants), Some(variant_id)) => variants.iter().any(|id| id == variant_id),
+        _ => true,
+    }
+}
+
+fn is_option_selected(
+    name: &str,
+    optional: bool,
+    default: bool,
+    options: &OptionSelections,
+) -> bool {
+    !optional || options.get(name).copied().unwrap_or(default)
+}
+
+fn validate_maven(
+    name: &str,
+    repository: &str,
+    coordinates: &str,
+    allowlist: &HostAllowlist,
+) -> Result<()> {
+    allowlist
+        .check_str(repository)
+        .with_context(|| format!("Repository of '{name}' is not allowed"))?;
+    MavenArtifact::parse(coordinates)
+        .with_context(|| format!("'{name}' has invalid coordinates"))?;
+    Ok(())
+}
+
+// Every entry needs a SHA-1 hash unless its source publishes one: CurseForge files,
+// which may give a fingerprint instead, and Maven artifacts. Installs fail when the
+// source does not provide the SHA-1 after all.
+fn validate_checksum(
+    name: &str,
+    source: &SourceType,
+    hash: &str,
+    fingerprint: Option<u32>,
+) -> Result<()> {
+    match (source, fingerprint) {
+        (SourceType::Curseforge { .. }, Some(_)) => Ok(()),
+        (_, Some(_)) => {
+            bail!("'{name}' has a fingerprint, which is only supported for CurseForge files")
+        }
+        // Verified with the repository's checksum files
+        (SourceType::Maven { .. }, None) => Ok(()),
+        (_, None) if hash.trim().is_empty() => bail!("'{name}' must have a hash"),
+        (_, None) => Ok(()),
+    }
+}
+
+fn resolve_local_path(config_dir: &Path, path: &str) -> Result<PathBuf> {
+    let candidates = [
+        config_dir.join(path),
+        config_dir.join(PAYLOAD_DIR).join(path),
+    ];
+    match candidates.iter().find(|candidate| candidate.is_file()) {
+        Some(candidate) => Ok(candidate.clone()),
+        None => bail!(
+            "Local file {path} was not found in {} or {}",
+            config_dir.display(),
+            config_dir.join(PAYLOAD_DIR).display()
+        ),
+    }
+}
+
 fn validate_relative_dir(dir: &str, field: &str) -> Result<()> {
     let path = Path::new(dir);
     if path.is_absolute() {
@@ -260,3 +916,279 @@ fn validate_relative_dir(dir: &str, field: &str) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = r#"
+schemaVersion: 4
+packVersion: "1.0.0"
+profile:
+  name: Test
+  icon: base.png
+  version: neoforge-21.1.215
+modLoader:
+  name: NeoForge
+  url: https://github.com/example/loader/releases/download/v1/installer.jar
+  hash: 0000000000000000000000000000000000000000
+"#;
+
+    fn load(body: &str) -> Result<ModPackConfig> {
+        ModPackConfig::load_from_str(&format!("{HEADER}{body}"))
+    }
+
+    // A GitHub mod entry with extra YAML fields, one per line
+    fn mod_entry(name: &str, fields: &[&str]) -> String {
+        let mut entry = format!(
+            "  - name: {name}\n    type: github\n    repo: example/mods\n    tag: v1\n    \
+             asset: {name}.jar\n    hash: 1111111111111111111111111111111111111111\n    \
+             side: both\n"
+        );
+        for field in fields {
+            entry.push_str(&format!("    {field}\n"));
+        }
+        entry
+    }
+
+    fn selections(options: &[(&str, bool)]) -> OptionSelections {
+        options
+            .iter()
+            .map(|(name, selected)| (name.to_string(), *selected))
+            .collect()
+    }
+
+    fn pick_one_config(default_member: Option<&str>) -> ModPackConfig {
+        let fields = |name: &str| {
+            let mut fields = vec!["optional: true", "group: shaders"];
+            if default_member == Some(name) {
+                fields.push("default: true");
+            }
+            mod_entry(name, &fields)
+        };
+        load(&format!(
+            "optionGroups:\n  - id: shaders\n    name: Shaders\n    pickOne: true\nmods:\n{}{}",
+            fields("Iris"),
+            fields("Oculus")
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn picks_default_member_when_none_is_selected() {
+        let config = pick_one_config(Some("Oculus"));
+        let resolved = config
+            .resolve_options(&selections(&[("Iris", false), ("Oculus", false)]))
+            .unwrap();
+        assert_eq!(resolved, selections(&[("Iris", false), ("Oculus", true)]));
+    }
+
+    #[test]
+    fn picks_first_member_without_a_default() {
+        let config = pick_one_config(None);
+        let resolved = config.resolve_options(&OptionSelections::new()).unwrap();
+        assert_eq!(resolved, selections(&[("Iris", true), ("Oculus", false)]));
+    }
+
+    #[test]
+    fn keeps_a_single_selection() {
+        let config = pick_one_config(Some("Oculus"));
+        let resolved = config
+            .resolve_options(&selections(&[("Iris", true), ("Oculus", false)]))
+            .unwrap();
+        assert_eq!(resolved, selections(&[("Iris", true), ("Oculus", false)]));
+    }
+
+    #[test]
+    fn rejects_two_selections() {
+        let config = pick_one_config(None);
+        let result = config.resolve_options(&selections(&[("Iris", true), ("Oculus", true)]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_two_default_members() {
+        let body = format!(
+            "optionGroups:\n  - id: shaders\n    name: Shaders\n    pickOne: true\nmods:\n{}{}",
+            mod_entry(
+                "Iris",
+                &["optional: true", "group: shaders", "default: true"]
+            ),
+            mod_entry(
+                "Oculus",
+                &["optional: true", "group: shaders", "default: true"]
+            )
+        );
+        assert!(load(&body).is_err());
+    }
+
+    fn variant_config() -> ModPackConfig {
+        load(&format!(
+            "variants:\n  - id: lite\n    name: Lite\n    default: true\n    exclude: [Shaders]\n  \
+             - id: full\n    name: Full\n    include: [Distant Horizons]\n    profile:\n      \
+             version: neoforge-21.1.220\n      jvmArgs: -Xmx8G\nmods:\n{}{}{}",
+            mod_entry("Core", &[]),
+            mod_entry("Shaders", &[]),
+            mod_entry("Distant Horizons", &[])
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn uses_default_variant_unless_another_is_selected() {
+        let config = variant_config();
+        let default = config.resolve_selection(&Selection::default()).unwrap();
+        assert_eq!(default.variant.as_deref(), Some("lite"));
+        let selected = Selection {
+            variant: Some("full".to_string()),
+            ..Selection::default()
+        };
+        let resolved = config.resolve_selection(&selected).unwrap();
+        assert_eq!(resolved.variant.as_deref(), Some("full"));
+        let unknown = Selection {
+            variant: Some("removed".to_string()),
+            ..Selection::default()
+        };
+        let resolved = config.resolve_selection(&unknown).unwrap();
+        assert_eq!(resolved.variant.as_deref(), Some("lite"));
+    }
+
+    #[test]
+    fn variant_profile_overrides_the_default_profile() {
+        let config = variant_config();
+        let profile = config.get_variant_profile(Some("full"));
+        assert_eq!(profile.version, "neoforge-21.1.220");
+        assert_eq!(profile.jvm_args.as_deref(), Some("-Xmx8G"));
+        assert_eq!(profile.icon, "base.png");
+        let profile = config.get_variant_profile(Some("lite"));
+        assert_eq!(profile.version, "neoforge-21.1.215");
+        assert_eq!(profile.jvm_args, None);
+    }
+
+    #[test]
+    fn assigns_entries_to_variants() {
+        let config = variant_config();
+        let members = |name: &str| {
+            let entry = config.get_mods().iter().find(|e| e.name == name).unwrap();
+            (
+                entry.is_in_variant(Some("lite")),
+                entry.is_in_variant(Some("full")),
+            )
+        };
+        assert_eq!(members("Core"), (true, true));
+        assert_eq!(members("Shaders"), (false, true));
+        // Included somewhere, so only in the variants including it
+        assert_eq!(members("Distant Horizons"), (false, true));
+    }
+
+    #[test]
+    fn rejects_variants_referring_to_unknown_entries() {
+        let body = format!(
+            "variants:\n  - id: lite\n    name: Lite\n    exclude: [Missing]\nmods:\n{}",
+            mod_entry("Core", &[])
+        );
+        assert!(load(&body).is_err());
+    }
+
+    fn other_os() -> Os {
+        match Os::current() {
+            Some(Os::Windows) => Os::Linux,
+            _ => Os::Windows,
+        }
+    }
+
+    fn other_arch() -> Arch {
+        match Arch::current() {
+            Some(Arch::Aarch64) => Arch::X86_64,
+            _ => Arch::Aarch64,
+        }
+    }
+
+    #[test]
+    fn matches_any_platform_without_conditions() {
+        assert!(is_platform_matched(None, None));
+    }
+
+    #[test]
+    fn matches_current_os_and_arch() {
+        let os = [Os::current().unwrap(), other_os()];
+        let arch = [Arch::current().unwrap()];
+        assert!(is_platform_matched(Some(&os), None));
+        assert!(is_platform_matched(None, Some(&arch)));
+        assert!(is_platform_matched(Some(&os), Some(&arch)));
+    }
+
+    #[test]
+    fn rejects_other_os_or_arch() {
+        assert!(!is_platform_matched(Some(&[other_os()]), None));
+        assert!(!is_platform_matched(None, Some(&[other_arch()])));
+        assert!(!is_platform_matched(
+            Some(&[Os::current().unwrap()]),
+            Some(&[other_arch()])
+        ));
+    }
+
+    #[test]
+    fn skips_entries_for_other_platforms() {
+        let config = load(&format!(
+            "mods:\n{}{}",
+            mod_entry("Everywhere", &[]),
+            mod_entry("Elsewhere", &[&format!("os: [{}]", other_os_name())])
+        ))
+        .unwrap();
+        let installed: Vec<&str> = config
+            .get_mods()
+            .iter()
+            .filter(|entry| entry.should_install(&Side::Client, &Selection::default()))
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(installed, ["Everywhere"]);
+    }
+
+    fn other_os_name() -> String {
+        serde_yaml::to_string(&other_os())
+            .unwrap()
+            .trim()
+            .to_string()
+    }
+
+    const CHANNELS: &str = "channels:
+  - id: stable
+    name: Stable
+    updateUrl: https://github.com/example/pack/releases/download/stable/config.yaml
+  - id: beta
+    name: Beta
+    updateUrl: https://github.com/example/pack/releases/download/beta/config.yaml
+";
+
+    #[test]
+    fn uses_update_url_of_selected_channel() {
+        let config = load(CHANNELS).unwrap();
+        assert_eq!(
+            config.get_update_url(Some("beta")),
+            Some("https://github.com/example/pack/releases/download/beta/config.yaml")
+        );
+    }
+
+    #[test]
+    fn falls_back_to_default_update_url() {
+        let stable = Some("https://github.com/example/pack/releases/download/stable/config.yaml");
+        let config = load(CHANNELS).unwrap();
+        assert_eq!(config.get_update_url(None), stable);
+        assert_eq!(config.get_update_url(Some("nightly")), stable);
+        let config = load(&format!(
+            "updateUrl: https://github.com/example/pack/releases/download/main/config.yaml\n{CHANNELS}"
+        ))
+        .unwrap();
+        assert_eq!(
+            config.get_update_url(Some("nightly")),
+            Some("https://github.com/example/pack/releases/download/main/config.yaml")
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_channels() {
+        let body = CHANNELS.replace("id: beta", "id: stable");
+        assert!(load(&body).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use std::env;
+use std::fmt::{self, Debug, Display};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::downloader::RemoteFile;
+use crate::network::{self, HostAllowlist};
+
+const DEFAULT_BASE_URL: &str = "https://api.curseforge.com";
+// Overrides the API base URL, e.g. to point at a local stand-in
+const BASE_URL_ENV: &str = "CURSEFORGE_API_URL";
+// Read once at startup
+pub const API_KEY_ENV: &str = "CURSEFORGE_API_KEY";
+const HASH_ALGO_SHA1: u32 = 1;
+const FINGERPRINT_SEED: u32 = 1;
+const FINGERPRINT_BUFFER_SIZE: usize = 64 * 1024;
+
+// Kept in memory only and never printed
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn new(key: &str) -> Option<Self> {
+        let key = key.trim();
+        (!key.is_empty()).then(|| Self(key.to_string()))
+    }
+}
+
+impl Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ApiKey(***)")
+    }
+}
+
+// A file whose author disallows distribution through third-party apps
+#[derive(Debug, Clone)]
+pub struct RestrictedFile {
+    pub project_id: u32,
+    pub file_id: u32,
+    pub file_name: String,
+    pub file_length: u64,
+    // Published even when the download is not
+    pub sha1: Option<String>,
+    pub website_url: Option<String>,
+}
+
+impl Display for RestrictedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CurseForge file {} ({}/{}) cannot be downloaded automatically because its author disallows third-party distribution.",
+            self.file_name, self.project_id, self.file_id
+        )?;
+        if let Some(website_url) = &self.website_url {
+            write!(f, " Download it manually from {website_url}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RestrictedFile {}
+
+#[derive(Deserialize)]
+struct Response<T> {
+    data: T,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeFile {
+    pub mod_id: u32,
+    pub file_name: String,
+    pub file_length: u64,
+    #[serde(default)]
+    pub hashes: Vec<FileHash>,
+    #[serde(default)]
+    pub file_fingerprint: Option<u32>,
+    // Null when the author disallows third-party distribution
+    pub download_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FileHash {
+    pub value: String,
+    pub algo: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeMod {
+    links: ModLinks,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModLinks {
+    website_url: Option<String>,
+}
+
+pub struct CurseForge {
+    client: Client,
+    base_url: String,
+    api_key: Option<Arc<ApiKey>>,
+}
+
+impl CurseForge {
+    pub fn new(api_key: Option<Arc<ApiKey>>, allowlist: &HostAllowlist) -> Result<Self> {
+        let base_url = env::var(BASE_URL_ENV)
+            .ok()
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        if base_url != DEFAULT_BASE_URL {
+            allowlist
+                .check_str(&base_url)
+                .with_context(|| format!("{BASE_URL_ENV} is not allowed"))?;
+            log::info!("Using CurseForge API at {base_url}");
+        }
+        Ok(Self {
+            client: network::client_builder()?
+                .redirect(allowlist.redirect_policy())
+                .build()
+                .context("Failed to build HTTP client")?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        })
+    }
+
+    pub fn set_api_key(&mut self, api_key: Option<Arc<ApiKey>>) {
+        self.api_key = api_key;
+    }
+
+    pub async fn get_file(&self, project_id: u32, file_id: u32) -> Result<CurseForgeFile> {
+        let file: CurseForgeFile = self
+            .get(&format!("/v1/mods/{project_id}/files/{file_id}"))
+            .await?;
+        if file.mod_id != project_id {
+            bail!(
+                "Project ID mismatch: expected {}, got {}.",
+                project_id,
+                file.mod_id
+            );
+        }
+        Ok(file)
+    }
+
+    // Returns the file with the hashes, size and file name published by CurseForge.
+    // Fails with `RestrictedFile` when the file has to be downloaded manually.
+    pub async fn get_remote_file(&self, project_id: u32, file_id: u32) -> Result<RemoteFile> {
+        let file = self.get_file(project_id, file_id).await?;
+        let sha1 = file
+            .hashes
+            .iter()
+            .find(|hash| hash.algo == HASH_ALGO_SHA1)
+            .map(|hash| hash.value.clone());
+        let Some(url) = file.download_url.clone() else {
+            let website_url = self
+                .get::<CurseForgeMod>(&format!("/v1/mods/{project_id}"))
+                .await
+                .inspect_err(|e| log::warn!("Failed to get CurseForge project {project_id}: {e:?}"))
+                .ok()
+                .and_then(|project| project.links.website_url);
+            return Err(RestrictedFile {
+                project_id,
+                file_id,
+                file_name: file.file_name,
+                file_length: file.file_length,
+                sha1,
+                website_url,
+            }
+            .into());
+        };
+        Ok(RemoteFile {
+            url,
+            sha1,
+            file_name: Some(file.file_name),
+            size: Some(file.file_length),
+            fingerprint: file.file_fingerprint,
+            ..Default::default()
+        })
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let Some(api_key) = &self.api_key else {
+            bail!(
+                "CurseForge API key is not set. Start the installer with {API_KEY_ENV} set to download CurseForge mods."
+            );
+        };
+        let url = format!("{}{path}", self.base_url);
+        let response = self
+            .client
+            .get(&url)
+            .header("x-api-key", &api_key.0)
+            .header("accept", "application/json")
+            .send()
+            .await
+            .with_context(|| format!("Failed to request {url}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("Request to {url} failed with status {status}.");
+        }
+        let body: Response<T> = response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse response from {url}"))?;
+        Ok(body.data)
+    }
+}
+
+// CurseForge's file fingerprint: MurmurHash2 (seed 1) of the content without
+// tab, line feed, carriage return and space bytes. The file is read twice, as
+// the hash starts from the filtered length.
+pub fn fingerprint_file(path: &Path) -> Result<u32> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    fingerprint_reader(&mut file).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+fn fingerprint_reader<R: Read + Seek>(reader: &mut R) -> Result<u32> {
+    let mut buffer = vec![0; FINGERPRINT_BUFFER_SIZE];
+    let mut length: u32 = 0;
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        let count = buffer[..read]
+            .iter()
+            .filter(|b| !is_whitespace(**b))
+            .count();
+        length = length.wrapping_add(count as u32);
+    }
+    reader.seek(SeekFrom::Start(0))?;
+    let mut hasher = Murmur2::new(FINGERPRINT_SEED ^ length);
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        for b in buffer[..read].iter().filter(|b| !is_whitespace(**b)) {
+            hasher.push(*b);
+        }
+    }
+    Ok(hasher.finish())
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b'\t' | b'\n' | b'\r' | b' ')
+}
+
+struct Murmur2 {
+    hash: u32,
+    tail: [u8; 4],
+    tail_len: usize,
+}
+
+impl Murmur2 {
+    const M: u32 = 0x5bd1e995;
+
+    fn new(hash: u32) -> Self {
+        Self {
+            hash,
+            tail: [0; 4],
+            tail_len: 0,
+        }
+    }
+
+    fn push(&mut self, b: u8) {
+        self.tail[self.tail_len] = b;
+        self.tail_len += 1;
+        if self.tail_len == 4 {
+            let mut k = u32::from_le_bytes(self.tail);
+            k = k.wrapping_mul(Self::M);
+            k ^= k >> 24;
+            k = k.wrapping_mul(Self::M);
+            self.hash = self.hash.wrapping_mul(Self::M) ^ k;
+            self.tail_len = 0;
+        }
+    }
+
+    fn finish(self) -> u32 {
+        let mut hash = self.hash;
+        if self.tail_len > 0 {
+            for (i, b) in self.tail[..self.tail_len].iter().enumerate() {
+                hash ^= (*b as u32) << (8 * i);
+            }
+            hash = hash.wrapping_mul(Self::M);
+        }
+        hash ^= hash >> 13;
+        hash = hash.wrapping_mul(Self::M);
+        hash ^ (hash >> 15)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn fingerprint(content: &[u8]) -> u32 {
+        fingerprint_reader(&mut Cursor::new(content)).unwrap()
+    }
+
+    #[test]
+    fn computes_murmur2_fingerprints() {
+        assert_eq!(fingerprint(b""), 1540447798);
+        assert_eq!(fingerprint(b"a"), 626045324);
+        assert_eq!(fingerprint(b"abcd"), 3376380438);
+        assert_eq!(
+            fingerprint(b"The quick brown fox jumps over the lazy dog"),
+            3751777527
+        );
+    }
+
+    #[test]
+    fn ignores_whitespace_in_fingerprints() {
+        assert_eq!(fingerprint(b"hello world"), 2824650221);
+        assert_eq!(fingerprint(b"hello\tworld\r\n"), fingerprint(b"helloworld"));
+    }
+
+    #[test]
+    fn fingerprints_content_larger_than_the_buffer() {
+        let content: Vec<u8> = (0..FINGERPRINT_BUFFER_SIZE * 2 + 7)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        assert_eq!(fingerprint(&content), 3320929498);
+    }
+}
@@ -1,18 +1,120 @@
 use std::{
     fs::{self, File},
-    io::Write,
+    io::{Read, Write},
     path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{bail, Context, Result};
 use futures_util::StreamExt;
-use reqwest::{Client, Response};
+use reqwest::{redirect::Policy, Client, Response};
 use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha512};
+
+use crate::cache::{self, DownloadCache};
+use crate::network::{self, BandwidthLimiter, HostAllowlist};
 use urlencoding;
 
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+// A LAN peer that is down should not hold up every download
+const PEER_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
 #[derive(Clone)]
 pub struct DownloadManager {
     client: Client,
+    cache: Option<DownloadCache>,
+    peer_client: Client,
+    limiter: Option<Arc<BandwidthLimiter>>,
+    allowlist: HostAllowlist,
+    // Another installer's cache server, tried before the origin
+    peer: Option<String>,
+}
+
+// A file to download and what its host publishes about it
+#[derive(Debug, Clone, Default)]
+pub struct RemoteFile {
+    pub url: String,
+    pub file_name: Option<String>,
+    pub sha1: Option<String>,
+    // Set when `sha1` comes from the pack config rather than the host
+    pub is_sha1_pinned: bool,
+    pub sha256: Option<String>,
+    pub sha512: Option<String>,
+    pub size: Option<u64>,
+    // CurseForge murmur2 fingerprint
+    pub fingerprint: Option<u32>,
+    // Set only for local sources and bundled files, which are copied instead of
+    // downloaded. A `file://` URL is never treated as local.
+    pub local_path: Option<PathBuf>,
+}
+
+impl RemoteFile {
+    pub fn from_url(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Local files are "downloaded" by copying them through the same pipeline
+    pub fn from_local_path(path: &Path) -> Result<Self> {
+        let size = fs::metadata(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+            .len();
+        Ok(Self {
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string()),
+            size: Some(size),
+            local_path: Some(path.to_path_buf()),
+            ..Default::default()
+        })
+    }
+
+    // Where the file comes from, for logs
+    pub fn location(&self) -> String {
+        match &self.local_path {
+            Some(path) => path.display().to_string(),
+            None => self.url.clone(),
+        }
+    }
+
+    pub fn with_expected_sha1(&self, sha1: &str) -> Self {
+        if sha1.is_empty() {
+            return self.clone();
+        }
+        Self {
+            sha1: Some(sha1.to_ascii_lowercase()),
+            is_sha1_pinned: true,
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Default)]
+struct Hashers {
+    sha1: Sha1,
+    sha256: Sha256,
+    sha512: Sha512,
+}
+
+impl Hashers {
+    fn update(&mut self, chunk: &[u8]) {
+        self.sha1.update(chunk);
+        self.sha256.update(chunk);
+        self.sha512.update(chunk);
+    }
+
+    fn finish(self, path: PathBuf, size: u64) -> DownloadOutcome {
+        DownloadOutcome {
+            path,
+            hash: hex::encode(self.sha1.finalize()),
+            sha256: hex::encode(self.sha256.finalize()),
+            sha512: hex::encode(self.sha512.finalize()),
+            size,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -22,64 +124,317 @@ pub struct DownloadProgress {
 }
 
 impl DownloadManager {
-    pub fn new() -> Result<Self> {
-        let client = Client::builder()
+    pub fn new(allowlist: HostAllowlist) -> Result<Self> {
+        let client = network::client_builder()?
+            .redirect(allowlist.redirect_policy())
             .build()
             .context("Failed to build HTTP client")?;
-        Ok(Self { client })
+        // LAN peers are reached directly, without the proxy or bandwidth limit
+        let peer_client = network::client_builder()?
+            .no_proxy()
+            .redirect(Policy::none())
+            .connect_timeout(PEER_CONNECT_TIMEOUT)
+            .build()
+            .context("Failed to build HTTP client")?;
+        Ok(Self {
+            client,
+            cache: DownloadCache::open_default(),
+            peer_client,
+            limiter: network::bandwidth_limiter(),
+            allowlist,
+            peer: None,
+        })
+    }
+
+    pub fn set_peer(&mut self, peer: Option<String>) {
+        self.peer = peer.map(|peer| peer.trim().trim_end_matches('/').to_string());
+    }
+
+    // Keeps a verified download for later runs and other instances
+    pub fn store_in_cache(&self, remote_file: &RemoteFile, outcome: &DownloadOutcome) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        if remote_file.local_path.is_some() {
+            return;
+        }
+        if let Err(e) = cache.store(&outcome.hash, &outcome.path) {
+            log::warn!("Failed to cache {}: {e:?}", outcome.path.display());
+        }
+    }
+
+    // Places a cached copy in `temp_dir` when the expected SHA-1 is known
+    fn find_in_cache(
+        &self,
+        remote_file: &RemoteFile,
+        temp_dir: &Path,
+    ) -> Result<Option<DownloadOutcome>> {
+        let (Some(cache), Some(sha1)) = (&self.cache, &remote_file.sha1) else {
+            return Ok(None);
+        };
+        let Some(cached_path) = cache.find(sha1) else {
+            return Ok(None);
+        };
+        let file_name = match &remote_file.file_name {
+            Some(file_name) => file_name.into(),
+            None => cached_path
+                .file_name()
+                .with_context(|| format!("No file name in {}", cached_path.display()))?
+                .to_os_string(),
+        };
+        let destination = temp_dir.join(file_name);
+        fs::create_dir_all(temp_dir)
+            .with_context(|| format!("Failed to create directory {}", temp_dir.display()))?;
+        if destination.exists() {
+            fs::remove_file(&destination)?;
+        }
+        cache::link_or_copy(&cached_path, &destination)?;
+        let outcome = read_outcome(&destination)?;
+        // The cached file may have been modified through a hard link
+        if !outcome.hash.eq_ignore_ascii_case(sha1) {
+            log::warn!(
+                "Cached file {} is corrupted, removing it.",
+                cached_path.display()
+            );
+            fs::remove_file(&destination)?;
+            cache.remove(sha1)?;
+            return Ok(None);
+        }
+        log::info!("Using cached file {}", cached_path.display());
+        Ok(Some(outcome))
     }
 
     pub async fn download_to_dir<F>(
         &self,
-        url: &str,
+        remote_file: &RemoteFile,
         temp_dir: &Path,
         mut progress_callback: Option<F>,
     ) -> Result<DownloadOutcome>
     where
         F: FnMut(DownloadProgress) -> Result<()>,
     {
-        let response = self
-            .client
-            .get(url)
-            .send()
-            .await
-            .with_context(|| format!("Failed to download from {url}"))?;
-        let response = ensure_success(response, url).await?;
-        let file_name = extract_file_name(&response)?;
-        let destination = temp_dir.join(&file_name);
-        fs::create_dir_all(temp_dir)
-            .with_context(|| format!("Failed to create directory {}", temp_dir.display()))?;
-        let mut file = File::create(&destination).with_context(|| {
-            format!(
-                "Failed to create destination file {}",
-                destination.display()
-            )
-        })?;
-        let total_bytes = response.content_length();
-        let mut received_bytes = 0u64;
-        let mut hasher = Sha1::new();
-        let mut stream = response.bytes_stream();
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.with_context(|| format!("Failed to read chunk from {url}"))?;
-            file.write_all(&chunk)?;
-            hasher.update(&chunk);
-            received_bytes += chunk.len() as u64;
-            if let Some(callback) = progress_callback.as_mut() {
-                callback(DownloadProgress {
-                    received_bytes,
-                    total_bytes,
-                })?;
+        if let Some(source) = &remote_file.local_path {
+            return copy_to_dir(source, remote_file, temp_dir, progress_callback);
+        }
+        // Checked up front so a disallowed URL is refused even when the cache or a
+        // peer has the file
+        self.allowlist.check_str(&remote_file.url)?;
+        match self.find_in_cache(remote_file, temp_dir) {
+            Ok(Some(outcome)) => {
+                if let Some(callback) = progress_callback.as_mut() {
+                    callback(DownloadProgress {
+                        received_bytes: outcome.size,
+                        total_bytes: Some(outcome.size),
+                    })?;
+                }
+                return Ok(outcome);
             }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to use download cache: {e:?}"),
+        }
+        // The peer may be plain HTTP on any host, so it is only trusted with files
+        // whose SHA-1 the pack config pins. The allowlist above applies to the origin.
+        match self
+            .download_from_peer(remote_file, temp_dir, &mut progress_callback)
+            .await
+        {
+            Ok(Some(outcome)) => return Ok(outcome),
+            Ok(None) => {}
+            // Usually the peer does not have the file yet
+            Err(e) => log::info!("Not downloaded from LAN peer: {e:#}"),
         }
-        file.flush()?;
-        let hash_bytes = hasher.finalize();
-        let hash = hex::encode(hash_bytes);
+        fetch(
+            &self.client,
+            self.limiter.as_deref(),
+            &remote_file.url,
+            remote_file,
+            temp_dir,
+            &mut progress_callback,
+        )
+        .await
+    }
+
+    // Falls back to the origin when the peer does not have the file or sends
+    // something else
+    async fn download_from_peer<F>(
+        &self,
+        remote_file: &RemoteFile,
+        temp_dir: &Path,
+        progress_callback: &mut Option<F>,
+    ) -> Result<Option<DownloadOutcome>>
+    where
+        F: FnMut(DownloadProgress) -> Result<()>,
+    {
+        let (Some(peer), Some(sha1)) = (&self.peer, &remote_file.sha1) else {
+            return Ok(None);
+        };
+        if !remote_file.is_sha1_pinned {
+            return Ok(None);
+        }
+        let url = format!("{peer}/sha1/{}", sha1.to_ascii_lowercase());
+        let outcome = fetch(
+            &self.peer_client,
+            None,
+            &url,
+            remote_file,
+            temp_dir,
+            progress_callback,
+        )
+        .await?;
+        if !outcome.hash.eq_ignore_ascii_case(sha1) {
+            log::warn!("LAN peer sent a different file for {url}, downloading from origin");
+            fs::remove_file(&outcome.path)
+                .with_context(|| format!("Failed to remove {}", outcome.path.display()))?;
+            return Ok(None);
+        }
+        log::info!("Downloaded {} from LAN peer", outcome.path.display());
+        Ok(Some(outcome))
+    }
+}
+
+async fn fetch<F>(
+    client: &Client,
+    limiter: Option<&BandwidthLimiter>,
+    url: &str,
+    remote_file: &RemoteFile,
+    temp_dir: &Path,
+    progress_callback: &mut Option<F>,
+) -> Result<DownloadOutcome>
+where
+    F: FnMut(DownloadProgress) -> Result<()>,
+{
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to download from {url}"))?;
+    let response = ensure_success(response, url).await?;
+    log::info!(
+        "Downloading {url} from {}",
+        response.url().host_str().unwrap_or_default()
+    );
+    let file_name = match remote_file
+        .file_name
+        .as_ref()
+        .filter(|name| is_bare_file_name(name))
+    {
+        Some(file_name) => file_name.clone(),
+        None => extract_file_name(&response)?,
+    };
+    let destination = temp_dir.join(&file_name);
+    fs::create_dir_all(temp_dir)
+        .with_context(|| format!("Failed to create directory {}", temp_dir.display()))?;
+    let mut file = File::create(&destination).with_context(|| {
+        format!(
+            "Failed to create destination file {}",
+            destination.display()
+        )
+    })?;
+    let total_bytes = response.content_length();
+    let mut received_bytes = 0u64;
+    let mut hashers = Hashers::default();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.with_context(|| format!("Failed to read chunk from {url}"))?;
+        if let Some(limiter) = limiter {
+            limiter.consume(chunk.len()).await;
+        }
+        file.write_all(&chunk)?;
+        hashers.update(&chunk);
+        received_bytes += chunk.len() as u64;
+        if let Some(callback) = progress_callback.as_mut() {
+            callback(DownloadProgress {
+                received_bytes,
+                total_bytes,
+            })?;
+        }
+    }
+    file.flush()?;
 
-        Ok(DownloadOutcome {
-            path: destination.to_path_buf(),
-            hash,
+    Ok(hashers.finish(destination, received_bytes))
+}
+
+fn read_outcome(path: &Path) -> Result<DownloadOutcome> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hashers = Hashers::default();
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    let mut size = 0u64;
+    loop {
+        let length = file
+            .read(&mut buffer)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if length == 0 {
+            break;
+        }
+        hashers.update(&buffer[..length]);
+        size += length as u64;
+    }
+    Ok(hashers.finish(path.to_path_buf(), size))
+}
+
+fn copy_to_dir<F>(
+    source: &Path,
+    remote_file: &RemoteFile,
+    temp_dir: &Path,
+    mut progress_callback: Option<F>,
+) -> Result<DownloadOutcome>
+where
+    F: FnMut(DownloadProgress) -> Result<()>,
+{
+    let mut source_file =
+        File::open(source).with_context(|| format!("Failed to open {}", source.display()))?;
+    let file_name = remote_file
+        .file_name
+        .clone()
+        .or_else(|| {
+            source
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
         })
+        .with_context(|| format!("Could not determine file name of {}", source.display()))?;
+    if !is_bare_file_name(&file_name) {
+        bail!("Invalid file name for {}: {file_name}", source.display());
     }
+    let destination = temp_dir.join(&file_name);
+    fs::create_dir_all(temp_dir)
+        .with_context(|| format!("Failed to create directory {}", temp_dir.display()))?;
+    let mut file = File::create(&destination).with_context(|| {
+        format!(
+            "Failed to create destination file {}",
+            destination.display()
+        )
+    })?;
+    let total_bytes = source_file.metadata().ok().map(|metadata| metadata.len());
+    let mut received_bytes = 0u64;
+    let mut hashers = Hashers::default();
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        let length = source_file
+            .read(&mut buffer)
+            .with_context(|| format!("Failed to read {}", source.display()))?;
+        if length == 0 {
+            break;
+        }
+        let chunk = &buffer[..length];
+        file.write_all(chunk)?;
+        hashers.update(chunk);
+        received_bytes += length as u64;
+        if let Some(callback) = progress_callback.as_mut() {
+            callback(DownloadProgress {
+                received_bytes,
+                total_bytes,
+            })?;
+        }
+    }
+    file.flush()?;
+
+    Ok(hashers.finish(destination, received_bytes))
+}
+
+// Rejects names that would leave the directory they are joined to
+pub fn is_bare_file_name(file_name: &str) -> bool {
+    Path::new(file_name).file_name() == Some(file_name.as_ref())
 }
 
 fn extract_file_name(response: &Response) -> Result<String> {
@@ -91,7 +446,7 @@ fn extract_file_name(response: &Response) -> Result<String> {
                 let part = part.trim();
                 if let Some(file_name) = part.strip_prefix("filename=") {
                     let file_name = file_name.trim_matches('"').trim();
-                    if !file_name.is_empty() {
+                    if !file_name.is_empty() && is_bare_file_name(file_name) {
                         return Ok(file_name.to_string());
                     }
                 }
@@ -105,8 +460,9 @@ fn extract_file_name(response: &Response) -> Result<String> {
             if !last_segment.is_empty() {
                 // Remove query parameters if present
                 let file_name = last_segment.split('?').next().unwrap_or(last_segment);
-                if !file_name.is_empty() {
-                    return Ok(urlencoding::decode(file_name)?.to_string());
+                let file_name = urlencoding::decode(file_name)?;
+                if !file_name.is_empty() && is_bare_file_name(&file_name) {
+                    return Ok(file_name.to_string());
                 }
             }
         }
@@ -138,4 +494,47 @@ async fn ensure_success(response: Response, url: &str) -> Result<Response> {
 pub struct DownloadOutcome {
     pub path: PathBuf,
     pub hash: String,
+    pub sha256: String,
+    pub sha512: String,
+    pub size: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use futures_util::FutureExt;
+
+    use super::*;
+
+    const SHA1: &str = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
+
+    #[test]
+    fn skips_peer_for_sha1_published_by_host() {
+        let mut manager = DownloadManager::new(HostAllowlist::new(&[])).unwrap();
+        manager.set_peer(Some("http://192.0.2.1:8080".to_string()));
+        let remote_file = RemoteFile {
+            url: "https://cdn.modrinth.com/data/abc/versions/1/mod.jar".to_string(),
+            sha1: Some(SHA1.to_string()),
+            ..Default::default()
+        };
+        // Resolves without a request when the peer is skipped
+        let result = manager
+            .download_from_peer(
+                &remote_file,
+                &env::temp_dir(),
+                &mut None::<fn(DownloadProgress) -> Result<()>>,
+            )
+            .now_or_never();
+        assert!(matches!(result, Some(Ok(None))));
+    }
+
+    #[test]
+    fn pins_sha1_from_config() {
+        let remote_file = RemoteFile::from_url("https://example.com/mod.jar");
+        assert!(!remote_file.with_expected_sha1("").is_sha1_pinned);
+        let pinned = remote_file.with_expected_sha1(&SHA1.to_ascii_uppercase());
+        assert!(pinned.is_sha1_pinned);
+        assert_eq!(pinned.sha1.as_deref(), Some(SHA1));
+    }
 }
@@ -0,0 +1,98 @@
+use std::env;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::downloader::RemoteFile;
+use crate::network::{self, HostAllowlist};
+
+const DOWNLOAD_BASE_URL: &str = "https://github.com";
+// Resolves assets via the releases API instead of the conventional URL,
+// e.g. `https://api.github.com` or a GitHub Enterprise `https://<host>/api/v3`
+const API_URL_ENV: &str = "GITHUB_API_URL";
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    size: u64,
+    browser_download_url: String,
+}
+
+pub struct Github;
+
+impl Github {
+    // `repo` is `<owner>/<name>`
+    pub async fn get_remote_file(
+        repo: &str,
+        tag: &str,
+        asset: &str,
+        allowlist: &HostAllowlist,
+    ) -> Result<RemoteFile> {
+        match env::var(API_URL_ENV).ok().filter(|url| !url.is_empty()) {
+            Some(api_url) => {
+                allowlist
+                    .check_str(&api_url)
+                    .with_context(|| format!("{API_URL_ENV} is not allowed"))?;
+                Self::get_release_asset(&api_url, repo, tag, asset, allowlist).await
+            }
+            None => Ok(RemoteFile {
+                file_name: Some(asset.to_string()),
+                ..RemoteFile::from_url(&Self::get_download_url(repo, tag, asset))
+            }),
+        }
+    }
+
+    pub fn get_download_url(repo: &str, tag: &str, asset: &str) -> String {
+        format!(
+            "{DOWNLOAD_BASE_URL}/{repo}/releases/download/{}/{}",
+            urlencoding::encode(tag),
+            urlencoding::encode(asset)
+        )
+    }
+
+    async fn get_release_asset(
+        api_url: &str,
+        repo: &str,
+        tag: &str,
+        asset: &str,
+        allowlist: &HostAllowlist,
+    ) -> Result<RemoteFile> {
+        let url = format!(
+            "{}/repos/{repo}/releases/tags/{}",
+            api_url.trim_end_matches('/'),
+            urlencoding::encode(tag)
+        );
+        let response = network::client_builder()?
+            .redirect(allowlist.redirect_policy())
+            .build()
+            .context("Failed to build HTTP client")?
+            .get(&url)
+            .header("accept", "application/vnd.github+json")
+            .send()
+            .await
+            .with_context(|| format!("Failed to request {url}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("Request to {url} failed with status {status}.");
+        }
+        let release: Release = response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse response from {url}"))?;
+        let Some(release_asset) = release.assets.into_iter().find(|a| a.name == asset) else {
+            bail!("Release {tag} of {repo} has no asset named {asset}.");
+        };
+        Ok(RemoteFile {
+            url: release_asset.browser_download_url,
+            file_name: Some(release_asset.name),
+            size: Some(release_asset.size),
+            ..Default::default()
+        })
+    }
+}
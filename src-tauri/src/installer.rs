@@ -1,22 +1,34 @@
 use std::{
     cmp::Ordering,
+    collections::HashMap,
     env,
     fmt::{self, Display},
     fs::{self, File},
+    io,
     path::{Path, PathBuf},
     process::{Command, Stdio},
+    sync::Arc,
 };
 
 use anyhow::{anyhow, bail, Context, Result};
 use semver::Version;
 use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
 use tauri::{AppHandle, Emitter};
 use zip::ZipArchive;
 
-use crate::config::{ModPackConfig, ResourceEntry, Side};
-use crate::downloader::{DownloadManager, DownloadProgress};
+use crate::bundle::{Bundle, BUNDLE_DIR};
+use crate::compat::GameTarget;
+use crate::config::{ModPackConfig, Profile, ResourceEntry, Selection, Side, SourceType};
+use crate::curseforge::{self, ApiKey, CurseForge, RestrictedFile};
+use crate::downloader::{
+    is_bare_file_name, DownloadManager, DownloadOutcome, DownloadProgress, RemoteFile,
+};
+use crate::jar::{self, ModMetadata};
 use crate::launcher::{LauncherProfile, LauncherProfiles};
-use crate::state::{InstallerState, ModLoaderState, ModState, ResourceState};
+use crate::modrinth::{self, Modrinth};
+use crate::network::HostAllowlist;
+use crate::state::{InstallerState, ManualDownload, ModLoaderState, ModState, ResourceState};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -34,15 +46,43 @@ impl Display for InstallerMode {
     }
 }
 
+// Config archives applied by update_settings
+struct SettingsArchive {
+    url: &'static str,
+    hash: &'static str,
+}
+
+const CONFIGS_V1_2_0: SettingsArchive = SettingsArchive {
+    url: "https://github.com/kyazuki/Makibania-Modpack-Resources/releases/download/v1.2.0/configs.zip",
+    hash: "4cb14e94845a0f03775c0d1b8f3f0cbddb675ddb",
+};
+const CONFIGS_V1_2_1: SettingsArchive = SettingsArchive {
+    url: "https://github.com/kyazuki/Makibania-Modpack-Resources/releases/download/v1.2.1/configs.zip",
+    hash: "9e5f63a8b1a6da42792ffc1563dcd6c6f6eac495",
+};
+const SETTINGS_ARCHIVES: [SettingsArchive; 2] = [CONFIGS_V1_2_0, CONFIGS_V1_2_1];
+
 pub struct Installer {
     mode: InstallerMode,
     app: AppHandle,
     download_manager: DownloadManager,
+    curseforge: CurseForge,
+    // Also checks API base URLs overridden by environment variables
+    allowlist: HostAllowlist,
     config: ModPackConfig,
+    // Local sources are resolved against this directory
+    config_dir: PathBuf,
     install_dir: PathBuf,
     side: Side,
     temp_dir: PathBuf,
     state_path: PathBuf,
+    selection: Option<Selection>,
+    modrinth_cache_path: PathBuf,
+    // Modrinth files resolved in bulk, keyed by version ID
+    remote_files: HashMap<String, RemoteFile>,
+    // Satisfies downloads before the network is used
+    bundle: Option<Bundle>,
+    bundle_dir: PathBuf,
 }
 
 impl Installer {
@@ -56,18 +96,47 @@ impl Installer {
         state_path: PathBuf,
     ) -> Result<Self> {
         assert_ne!(&side, &Side::Both);
+        let config = ModPackConfig::load_from_path(&config_path)?;
+        let allowlist = config.get_allowed_hosts();
         Ok(Self {
             mode,
             app,
-            download_manager: DownloadManager::new()?,
-            config: ModPackConfig::load_from_path(&config_path)?,
+            curseforge: CurseForge::new(None, &allowlist)?,
+            download_manager: DownloadManager::new(allowlist.clone())?,
+            allowlist,
+            config,
+            config_dir: config_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
             install_dir: install_dir.clone(),
             side,
             temp_dir: app_dir.join(".temp"),
             state_path,
+            selection: None,
+            modrinth_cache_path: app_dir.join("modrinth-cache.json"),
+            remote_files: HashMap::new(),
+            bundle: Bundle::open(&install_dir.join(BUNDLE_DIR))?
+                .inspect(|_| log::info!("Found offline bundle, using it for downloads.")),
+            bundle_dir: install_dir.join(BUNDLE_DIR),
         })
     }
 
+    pub fn with_selection(mut self, selection: Option<Selection>) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    pub fn with_curseforge_api_key(mut self, api_key: Option<Arc<ApiKey>>) -> Self {
+        self.curseforge.set_api_key(api_key);
+        self
+    }
+
+    pub fn with_cache_peer(mut self, peer: Option<String>) -> Self {
+        self.download_manager.set_peer(peer);
+        self
+    }
+
     pub fn can_install(config_path: &Path, state_path: &Path) -> Result<()> {
         if !config_path.exists() {
             bail!("Config file is not found.");
@@ -88,19 +157,38 @@ impl Installer {
         }
     }
 
-    pub fn can_update(config_path: &Path, state_path: &Path) -> Result<()> {
+    pub fn can_update(
+        config_path: &Path,
+        state_path: &Path,
+        selection: Option<&Selection>,
+    ) -> Result<()> {
         let config = ModPackConfig::load_from_path(&config_path)?;
-        Self::can_update_state(&config, &state_path).map(|_| ())
+        Self::can_update_state(&config, &state_path, selection).map(|_| ())
     }
 
-    fn can_update_state(config: &ModPackConfig, state_path: &Path) -> Result<InstallerState> {
+    fn can_update_state(
+        config: &ModPackConfig,
+        state_path: &Path,
+        selection: Option<&Selection>,
+    ) -> Result<InstallerState> {
         if !state_path.exists() {
             bail!("Installer state file is not found.");
         }
         let state = InstallerState::load(&state_path)?;
         match state.get_process_mode() {
             None => {
-                if config.get_pack_version() > state.get_pack_version() {
+                let is_selection_changed = match selection {
+                    Some(selection) => {
+                        config.resolve_selection(selection)?
+                            != config.resolve_selection(state.get_selection())?
+                    }
+                    None => false,
+                };
+                // Switching channels may also move to an older pack version
+                if config.get_pack_version() > state.get_pack_version()
+                    || is_selection_changed
+                    || state.is_channel_changed()
+                {
                     Ok(state)
                 } else {
                     bail!("No update is needed.");
@@ -113,6 +201,482 @@ impl Installer {
         }
     }
 
+    pub fn can_change_options(config_path: &Path, state_path: &Path) -> Result<()> {
+        let config = ModPackConfig::load_from_path(config_path)?;
+        if !config.has_choices() {
+            bail!("Config has no variants or optional entries.");
+        }
+        if !state_path.exists() {
+            bail!("Installer state file is not found.");
+        }
+        let state = InstallerState::load(state_path)?;
+        if let Some(mode) = state.get_process_mode() {
+            bail!("Another mode ({:?}) is already in progress.", mode);
+        }
+        Ok(())
+    }
+
+    // Names of installed mods renamed to .disabled by the player
+    pub fn get_disabled_mods(install_dir: &Path, state_path: &Path) -> Result<Vec<String>> {
+        if !state_path.exists() {
+            return Ok(Vec::new());
+        }
+        let state = InstallerState::load(state_path)?;
+        let mods_dir = install_dir.join("mods");
+        Ok(state
+            .get_all_mods()
+            .iter()
+            .filter(|mod_state| {
+                let mod_path = mods_dir.join(&mod_state.file_name);
+                !mod_path.exists() && disabled_path(&mod_path).exists()
+            })
+            .map(|mod_state| {
+                if mod_state.name.is_empty() {
+                    mod_state.file_name.clone()
+                } else {
+                    mod_state.name.clone()
+                }
+            })
+            .collect())
+    }
+
+    // Moves a mod added by the player out of the mods folder
+    pub fn quarantine_mod(
+        install_dir: &Path,
+        app_dir: &Path,
+        state_path: &Path,
+        file_name: &str,
+    ) -> Result<PathBuf> {
+        if !is_bare_file_name(file_name) {
+            bail!("Invalid mod file name: {file_name}");
+        }
+        if state_path.exists() {
+            let state = InstallerState::load(state_path)?;
+            if state
+                .get_all_mods()
+                .iter()
+                .any(|mod_state| mod_state.file_name == file_name)
+            {
+                bail!("Mod {file_name} is managed by the installer.");
+            }
+        }
+        let mod_path = install_dir.join("mods").join(file_name);
+        if !mod_path.exists() {
+            bail!("Mod file is not found: {}", mod_path.display());
+        }
+        let quarantine_dir = app_dir.join("quarantine");
+        fs::create_dir_all(&quarantine_dir).with_context(|| {
+            format!(
+                "Failed to create quarantine directory {}",
+                quarantine_dir.display()
+            )
+        })?;
+        let quarantine_path = quarantine_dir.join(file_name);
+        move_file(&mod_path, &quarantine_path)?;
+        log::info!(
+            "Quarantined mod {} to {}",
+            mod_path.display(),
+            quarantine_path.display()
+        );
+        Ok(quarantine_path)
+    }
+
+    // Checks every mod against the Minecraft and loader versions of each profile for pack authors.
+    // Fetched Modrinth versions are collected in `modrinth_versions`, keyed by version ID.
+    pub async fn validate_compatibility(
+        config_path: &Path,
+        state_path: &Path,
+        modrinth_versions: &mut HashMap<String, modrinth::Version>,
+    ) -> Result<Vec<String>> {
+        let config = ModPackConfig::load_from_path(config_path)?;
+        let state = if state_path.exists() {
+            Some(InstallerState::load(state_path)?)
+        } else {
+            None
+        };
+        let mut variant_ids: Vec<Option<&str>> = config
+            .get_variants()
+            .iter()
+            .map(|variant| Some(variant.id.as_str()))
+            .collect();
+        if variant_ids.is_empty() {
+            variant_ids.push(None);
+        }
+        let mut errors = Vec::new();
+        for variant_id in variant_ids {
+            let profile = config.get_variant_profile(variant_id);
+            let target = GameTarget::parse(&profile.version, config.get_mod_loader());
+            log::info!(
+                "Checking compatibility with {}: {target:?}",
+                profile.version
+            );
+            let prefix = variant_id.map_or(String::new(), |id| format!("[{id}] "));
+            for mod_entry in config.get_mods() {
+                if !mod_entry.is_in_variant(variant_id) {
+                    continue;
+                }
+                let mut problems = Vec::new();
+                if let SourceType::Modrinth {
+                    project_id,
+                    version_id,
+                } = &mod_entry.source
+                {
+                    if !modrinth_versions.contains_key(version_id) {
+                        let version = Modrinth::get_version(project_id, version_id)
+                            .await
+                            .with_context(|| {
+                                format!("Failed to get Modrinth version of {}", mod_entry.name)
+                            })?;
+                        modrinth_versions.insert(version_id.clone(), version);
+                    }
+                    let version = &modrinth_versions[version_id];
+                    if let Some(file) = Modrinth::get_primary_file(version) {
+                        if !hash_matches(&mod_entry.hash, &file.hashes.sha1) {
+                            problems.push(format!(
+                                "{} has hash {}, but Modrinth publishes {}",
+                                mod_entry.name, mod_entry.hash, file.hashes.sha1
+                            ));
+                        }
+                    }
+                    problems.extend(target.check_modrinth(
+                        &mod_entry.name,
+                        &version.game_versions,
+                        &version.loaders,
+                    ));
+                }
+                if let Some(mod_state) = state.as_ref().and_then(|s| s.get_mod(mod_entry)) {
+                    if mod_state.equals(mod_entry, false) {
+                        for metadata in &mod_state.metadata {
+                            problems.extend(target.check_metadata(metadata));
+                        }
+                    }
+                }
+                errors.extend(
+                    problems
+                        .into_iter()
+                        .map(|problem| format!("{prefix}{problem}")),
+                );
+            }
+        }
+        Ok(errors)
+    }
+
+    pub fn get_manual_downloads(state_path: &Path) -> Result<Vec<ManualDownload>> {
+        if !state_path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(InstallerState::load(state_path)?
+            .get_manual_downloads()
+            .clone())
+    }
+
+    // Adopts files in `dir` (e.g. Downloads) that match a pending manual download.
+    // Returns the names of adopted entries.
+    pub fn scan_manual_downloads(
+        install_dir: &Path,
+        app_dir: &Path,
+        state_path: &Path,
+        dir: &Path,
+    ) -> Result<Vec<String>> {
+        let mut state = InstallerState::load(state_path)?;
+        let mut adopted = Vec::new();
+        if state.get_manual_downloads().is_empty() || !dir.is_dir() {
+            return Ok(adopted);
+        }
+        for entry in fs::read_dir(dir)
+            .with_context(|| format!("Failed to read directory {}", dir.display()))?
+        {
+            let path = entry?.path();
+            let Ok(size) = path.metadata().map(|m| m.len()) else {
+                continue;
+            };
+            // Hash only files whose size matches to keep polling cheap
+            if !path.is_file() || !state.get_manual_downloads().iter().any(|m| m.size == size) {
+                continue;
+            }
+            // A file still being written or locked must not stop the polling
+            let checksums =
+                hash_file(&path).and_then(|hash| Ok((hash, curseforge::fingerprint_file(&path)?)));
+            let (hash, fingerprint) = match checksums {
+                Ok(checksums) => checksums,
+                Err(e) => {
+                    log::warn!("Skipping {}: {e:?}", path.display());
+                    continue;
+                }
+            };
+            let Some(manual_download) = state
+                .get_manual_downloads()
+                .iter()
+                .find(|m| m.size == size && m.matches(&hash, fingerprint))
+                .cloned()
+            else {
+                continue;
+            };
+            log::info!(
+                "Found manual download {} at {}",
+                manual_download.name,
+                path.display()
+            );
+            Self::adopt_file(install_dir, app_dir, &mut state, &manual_download, &path)?;
+            if let Err(e) = fs::remove_file(&path) {
+                log::warn!("Failed to remove {}: {e:?}", path.display());
+            }
+            state.save(state_path)?;
+            adopted.push(manual_download.name);
+        }
+        Ok(adopted)
+    }
+
+    // Adopts a file the player picked in a file dialog. Returns the name of the adopted entry.
+    pub fn adopt_manual_download(
+        install_dir: &Path,
+        app_dir: &Path,
+        state_path: &Path,
+        path: &Path,
+    ) -> Result<String> {
+        let mut state = InstallerState::load(state_path)?;
+        let hash = hash_file(path)?;
+        let fingerprint = curseforge::fingerprint_file(path)?;
+        let Some(manual_download) = state
+            .get_manual_downloads()
+            .iter()
+            .find(|m| m.matches(&hash, fingerprint))
+            .cloned()
+        else {
+            bail!(
+                "The picked file does not match any file to download manually \
+                 (SHA-1 {hash}, fingerprint {fingerprint})."
+            );
+        };
+        log::info!(
+            "Adopting {} as manual download {}",
+            path.display(),
+            manual_download.name
+        );
+        Self::adopt_file(install_dir, app_dir, &mut state, &manual_download, path)?;
+        state.save(state_path)?;
+        Ok(manual_download.name)
+    }
+
+    // Copies a verified file through the temp directory into place
+    fn adopt_file(
+        install_dir: &Path,
+        app_dir: &Path,
+        state: &mut InstallerState,
+        manual_download: &ManualDownload,
+        path: &Path,
+    ) -> Result<()> {
+        let temp_path = Self::stage_manual_download(app_dir, manual_download)?;
+        fs::copy(path, &temp_path).with_context(|| {
+            format!(
+                "Failed to copy {} to {}",
+                path.display(),
+                temp_path.display()
+            )
+        })?;
+        Self::place_manual_download(install_dir, state, manual_download, &temp_path)
+    }
+
+    fn stage_manual_download(app_dir: &Path, manual_download: &ManualDownload) -> Result<PathBuf> {
+        // Also guards entries recorded before file names were validated
+        if !is_bare_file_name(&manual_download.file_name) {
+            bail!(
+                "Invalid file name for {}: {}",
+                manual_download.name,
+                manual_download.file_name
+            );
+        }
+        let temp_dir = app_dir.join(".temp");
+        fs::create_dir_all(&temp_dir)
+            .with_context(|| format!("Failed to create temp directory {}", temp_dir.display()))?;
+        Ok(temp_dir.join(&manual_download.file_name))
+    }
+
+    // Moves a verified manual download into place and records it as downloaded
+    fn place_manual_download(
+        install_dir: &Path,
+        state: &mut InstallerState,
+        manual_download: &ManualDownload,
+        temp_path: &Path,
+    ) -> Result<()> {
+        match &manual_download.target_dir {
+            None => {
+                let mod_path = install_dir.join("mods").join(&manual_download.file_name);
+                move_file(temp_path, &mod_path)?;
+                state.add_mod(ModState {
+                    name: manual_download.name.clone(),
+                    file_name: manual_download.file_name.clone(),
+                    source: manual_download.source.clone(),
+                    hash: manual_download.hash.clone(),
+                    fingerprint: manual_download.fingerprint,
+                    metadata: inspect_mod(&mod_path),
+                });
+            }
+            Some(target_dir) => {
+                let target_dir_path = install_dir.join(target_dir);
+                if manual_download.decompress {
+                    extract_zip(temp_path, &target_dir_path)?;
+                    if let Err(e) = fs::remove_file(temp_path) {
+                        log::warn!(
+                            "Failed to remove temporary file {}: {e:?}",
+                            temp_path.display()
+                        );
+                    }
+                } else {
+                    move_file(temp_path, &target_dir_path.join(&manual_download.file_name))?;
+                }
+                // World copies are placed by the resumed process
+                state.add_resource(ResourceState {
+                    file_name: manual_download.file_name.clone(),
+                    source: manual_download.source.clone(),
+                    hash: manual_download.hash.clone(),
+                    fingerprint: manual_download.fingerprint,
+                    target_dir: target_dir.clone(),
+                    decompress: manual_download.decompress,
+                    world_copies: Vec::new(),
+                });
+            }
+        }
+        state.remove_manual_download(manual_download);
+        log::info!("Adopted manual download {}.", manual_download.name);
+        Ok(())
+    }
+
+    fn apply_selection(&self, state: &mut InstallerState) -> Result<()> {
+        let selection = self
+            .config
+            .resolve_selection(self.selection.as_ref().unwrap_or(state.get_selection()))?;
+        log::info!("Selected variant and options: {selection:?}");
+        state.set_selection(selection);
+        Ok(())
+    }
+
+    // Downloads every entry of the config, whatever its side, variant, options and
+    // platform, into the bundle next to the installer. Returns the names of files that
+    // must be downloaded manually and therefore could not be bundled.
+    pub async fn create_bundle(mut self) -> Result<Vec<String>> {
+        log::info!(
+            "Creating offline bundle in {} ...",
+            self.bundle_dir.display()
+        );
+        // Files already in the bundle are kept, not used as their own source
+        self.bundle = None;
+        let mut bundle = Bundle::create(&self.bundle_dir)?;
+        self.prepare_temp_dir()?;
+
+        let loader = self.config.get_mod_loader();
+        let key = Bundle::url_key(&loader.source.get_url()?);
+        if !bundle.contains(&key) {
+            let remote_file = cross_check_remote_file(
+                loader.source.get_remote_file(&self.allowlist).await?,
+                &loader.name,
+                &loader.hash,
+                None,
+            )?;
+            self.add_to_bundle(&mut bundle, &key, &remote_file, &loader.name, &loader.hash)
+                .await?;
+        }
+        for archive in &SETTINGS_ARCHIVES {
+            let key = Bundle::url_key(archive.url);
+            if !bundle.contains(&key) {
+                let remote_file = RemoteFile::from_url(archive.url);
+                self.add_to_bundle(&mut bundle, &key, &remote_file, "configs", archive.hash)
+                    .await?;
+            }
+        }
+
+        let entries: Vec<(&str, &SourceType, &str, Option<u32>)> = self
+            .config
+            .get_mods()
+            .iter()
+            .map(|e| (e.name.as_str(), &e.source, e.hash.as_str(), e.fingerprint))
+            .chain(
+                self.config
+                    .get_resources()
+                    .iter()
+                    .map(|e| (e.name.as_str(), &e.source, e.hash.as_str(), e.fingerprint)),
+            )
+            .collect();
+        let versions: Vec<(&str, &str)> = entries
+            .iter()
+            .filter(|(_, source, _, _)| !bundle.contains(&source.key()))
+            .filter_map(|(_, source, _, _)| match source {
+                SourceType::Modrinth {
+                    project_id,
+                    version_id,
+                } => Some((project_id.as_str(), version_id.as_str())),
+                _ => None,
+            })
+            .collect();
+        if !versions.is_empty() {
+            self.remote_files =
+                Modrinth::resolve_remote_files(&versions, &self.modrinth_cache_path).await?;
+        }
+
+        let mut skipped = Vec::new();
+        for (name, source, hash, fingerprint) in entries {
+            let key = source.key();
+            if bundle.contains(&key) {
+                log::info!("{name} is already bundled, skipping.");
+                continue;
+            }
+            let remote_file = match self
+                .resolve_remote_file(source, name, hash, fingerprint)
+                .await
+            {
+                Ok(remote_file) => remote_file,
+                Err(e) => match e.downcast_ref::<RestrictedFile>() {
+                    Some(restricted) => {
+                        log::warn!("{restricted}");
+                        skipped.push(name.to_string());
+                        continue;
+                    }
+                    None => return Err(e),
+                },
+            };
+            self.add_to_bundle(&mut bundle, &key, &remote_file, name, hash)
+                .await?;
+        }
+        log::info!("Created offline bundle.");
+        Ok(skipped)
+    }
+
+    async fn add_to_bundle(
+        &self,
+        bundle: &mut Bundle,
+        key: &str,
+        remote_file: &RemoteFile,
+        name: &str,
+        expected_hash: &str,
+    ) -> Result<()> {
+        log::info!("Bundling {name} from {} ...", remote_file.location());
+        self.emit_change_detail(name);
+        let remote_file = &remote_file.with_expected_sha1(expected_hash);
+        // The recorded SHA-1 is trusted for entries without a hash, so it must be verified
+        if remote_file.sha1.is_none() && remote_file.sha256.is_none() {
+            bail!("{name} cannot be bundled because no SHA-1 or SHA-256 is known to verify it.");
+        }
+        let outcome = self
+            .download_manager
+            .download_to_dir(
+                remote_file,
+                &self.temp_dir,
+                None::<fn(DownloadProgress) -> Result<()>>,
+            )
+            .await?;
+        verify_download(remote_file, expected_hash, &outcome)?;
+        self.download_manager.store_in_cache(remote_file, &outcome);
+        let file_name = outcome
+            .path
+            .file_name()
+            .ok_or_else(|| anyhow!("Could not extract file name from downloaded file"))?
+            .to_string_lossy()
+            .to_string();
+        bundle.add(key, &file_name, &outcome.hash, outcome.size, &outcome.path)?;
+        // Saved after every file so an interrupted run can be resumed
+        bundle.save()
+    }
+
     pub async fn run(mut self) -> Result<()> {
         self.emit_progress(0.);
         match self.mode {
@@ -139,9 +703,11 @@ impl Installer {
             }
             s
         };
+        self.apply_selection(&mut state)?;
         state.set_process_mode(self.mode);
         state.save(&self.state_path)?;
         if !is_retry {
+            self.resolve_modrinth_files(&state).await?;
             let total_steps = self.total_download_steps(self.mode, &state);
             let mut completed_steps = 0u32;
             // Download Mod loader
@@ -160,9 +726,27 @@ impl Installer {
                     );
                 }
             } else {
+                let remote_file = match self.find_in_bundle(
+                    &Bundle::url_key(&loader_config.source.get_url()?),
+                    &loader_config.hash,
+                )? {
+                    Some(remote_file) => remote_file,
+                    None => {
+                        loader_config
+                            .source
+                            .get_remote_file(&self.allowlist)
+                            .await?
+                    }
+                };
+                let remote_file = cross_check_remote_file(
+                    remote_file,
+                    &loader_config.name,
+                    &loader_config.hash,
+                    None,
+                )?;
                 let file_name = self
                     .ensure_download(
-                        &loader_config.url,
+                        &remote_file,
                         &loader_config.name,
                         &loader_config.hash,
                         &self.install_dir,
@@ -173,7 +757,7 @@ impl Installer {
                     .await?;
                 state.set_mod_loader(ModLoaderState {
                     file_name,
-                    url: loader_config.url.clone(),
+                    url: loader_config.source.get_url()?,
                     hash: loader_config.hash.clone(),
                 });
                 state.save(&self.state_path)?;
@@ -189,11 +773,17 @@ impl Installer {
             self.download_resources(&mut state, &mut completed_steps, total_steps)
                 .await?;
             debug_assert_eq!(completed_steps, total_steps);
+            self.ensure_no_manual_downloads(&state)?;
         }
         self.emit_progress(1.);
+        self.check_duplicate_mods(&state);
+        self.check_compatibility(&state);
         // Add profile to launcher
         self.emit_change_phase(Phase::AddProfile);
-        if let Err(e) = self.add_launcher_profile() {
+        let profile = self
+            .config
+            .get_variant_profile(state.get_selection().variant.as_deref());
+        if let Err(e) = self.add_launcher_profile(&profile) {
             log::warn!("Failed to add launcher profile: {e:?}");
             self.emit_add_alert(AlertLevel::Warning, "alertOnFailedAddProfile");
         }
@@ -215,25 +805,53 @@ impl Installer {
     async fn run_update(&mut self) -> Result<()> {
         log::info!("Starting update...");
         self.prepare_temp_dir()?;
-        let mut state = Self::can_update_state(&self.config, &self.state_path)?;
+        let mut state =
+            Self::can_update_state(&self.config, &self.state_path, self.selection.as_ref())?;
+        let previous_profile = self
+            .config
+            .get_variant_profile(state.get_selection().variant.as_deref());
+        self.apply_selection(&mut state)?;
         state.set_process_mode(self.mode);
         state.save(&self.state_path)?;
+        // Switching variants may change profile fields
+        let profile = self
+            .config
+            .get_variant_profile(state.get_selection().variant.as_deref());
+        if profile != previous_profile {
+            if let Err(e) = self.update_launcher_profile(&profile) {
+                log::warn!("Failed to update launcher profile: {e:?}");
+                self.emit_add_alert(AlertLevel::Warning, "alertOnFailedUpdateProfile");
+            }
+        }
         let total_steps = self.total_download_steps(self.mode, &state);
         let mut completed_steps = 0u32;
         // Remove mods
         self.emit_change_phase(Phase::RemoveMods);
-        let mods_dir = self.get_mods_dir();
         let all_mods: Vec<ModState> = state.get_all_mods().into_iter().cloned().collect();
         for mod_state in all_mods {
-            if !self.config.has_mod(&mod_state.source) {
-                let mod_path = mods_dir.join(&mod_state.file_name);
-                if mod_path.exists() {
-                    log::info!("Removing mod: {}", mod_state.file_name);
-                    fs::remove_file(&mod_path).with_context(|| {
-                        format!("Failed to remove mod file: {}", mod_path.display())
-                    })?;
-                } else {
-                    log::warn!("Mod file to remove does not exist: {}", mod_path.display());
+            let is_wanted = self
+                .config
+                .get_mod(&mod_state.source)
+                .is_some_and(|mod_entry| {
+                    mod_entry.should_install(&self.side, state.get_selection())
+                });
+            if !is_wanted {
+                match self.find_mod_file(&mod_state.file_name) {
+                    Some((mod_path, is_disabled)) => {
+                        log::info!("Removing mod: {}", mod_path.display());
+                        fs::remove_file(&mod_path).with_context(|| {
+                            format!("Failed to remove mod file: {}", mod_path.display())
+                        })?;
+                        if is_disabled {
+                            state.add_pending_disabled_mod(&mod_state);
+                        }
+                    }
+                    None => {
+                        log::warn!(
+                            "Mod file to remove does not exist: {}",
+                            self.get_mods_dir().join(&mod_state.file_name).display()
+                        );
+                    }
                 }
                 state.remove_mod(&mod_state);
                 state.save(&self.state_path)?;
@@ -241,7 +859,45 @@ impl Installer {
             completed_steps += 1u32;
             self.emit_progress(completed_steps as f32 / total_steps as f32);
         }
+        // Remove resources no longer selected for this installation or dropped from the config
+        let all_resources: Vec<ResourceState> = state.get_all_resources().to_vec();
+        for resource_state in all_resources {
+            let is_removable = match self
+                .config
+                .get_resource(&resource_state.source, &resource_state.target_dir)
+            {
+                Some(resource_entry) => {
+                    !resource_entry.should_install(&self.side, state.get_selection())
+                }
+                None => true,
+            };
+            if !is_removable {
+                continue;
+            }
+            log::info!("Removing resource: {}", resource_state.file_name);
+            self.remove_world_copies(&resource_state)?;
+            let resource_path = self
+                .install_dir
+                .join(&resource_state.target_dir)
+                .join(&resource_state.file_name);
+            if resource_state.decompress {
+                log::warn!(
+                    "Extracted files of {} are not tracked and will be kept.",
+                    resource_state.file_name
+                );
+            } else if resource_path.exists() {
+                fs::remove_file(&resource_path).with_context(|| {
+                    format!(
+                        "Failed to remove resource file: {}",
+                        resource_path.display()
+                    )
+                })?;
+            }
+            state.remove_resource(&resource_state);
+            state.save(&self.state_path)?;
+        }
         // Add mods
+        self.resolve_modrinth_files(&state).await?;
         self.emit_change_phase(Phase::DownloadMods);
         self.download_mods(&mut state, &mut completed_steps, total_steps)
             .await?;
@@ -254,7 +910,10 @@ impl Installer {
         self.update_settings(&mut state, &mut completed_steps, total_steps)
             .await?;
         debug_assert_eq!(completed_steps, total_steps);
+        self.ensure_no_manual_downloads(&state)?;
         self.emit_progress(1.);
+        self.check_duplicate_mods(&state);
+        self.check_compatibility(&state);
         state.set_installer_version(&self.app.package_info().version);
         state.set_pack_version(&self.config.get_pack_version());
         state.finalize(&self.state_path)?;
@@ -340,13 +999,15 @@ impl Installer {
             .config
             .get_mods()
             .iter()
-            .filter(|mod_entry| mod_entry.should_install(&self.side))
+            .filter(|mod_entry| mod_entry.should_install(&self.side, state.get_selection()))
             .count() as u32;
         steps += self
             .config
             .get_resources()
             .iter()
-            .filter(|resource_entry| resource_entry.should_install(&self.side))
+            .filter(|resource_entry| {
+                resource_entry.should_install(&self.side, state.get_selection())
+            })
             .count() as u32;
         if mode == InstallerMode::Update {
             steps += Self::get_update_settings_steps(
@@ -359,7 +1020,7 @@ impl Installer {
 
     async fn ensure_download(
         &self,
-        url: &str,
+        remote_file: &RemoteFile,
         name: &str,
         expected_hash: &str,
         final_dir: &Path,
@@ -367,12 +1028,14 @@ impl Installer {
         completed_steps: u32,
         total_steps: u32,
     ) -> Result<String> {
-        log::info!("Downloading {name} from {url} ...");
+        log::info!("Downloading {name} from {} ...", remote_file.location());
         self.emit_change_detail(name);
+        // Lets the download cache look the file up by hash
+        let remote_file = &remote_file.with_expected_sha1(expected_hash);
         let outcome = self
             .download_manager
             .download_to_dir(
-                url,
+                remote_file,
                 &self.temp_dir,
                 Some(move |progress: DownloadProgress| -> Result<()> {
                     if progress.total_bytes.is_none() {
@@ -393,7 +1056,8 @@ impl Installer {
             .path
             .file_name()
             .ok_or_else(|| anyhow::anyhow!("Could not extract file name from downloaded file"))?;
-        verify_hash(expected_hash, &outcome.hash, &outcome.path)?;
+        verify_download(remote_file, expected_hash, &outcome)?;
+        self.download_manager.store_in_cache(remote_file, &outcome);
         if !is_decompress {
             let final_path = final_dir.join(&file_name);
             move_file(&outcome.path, &final_path)?;
@@ -415,10 +1079,264 @@ impl Installer {
         Ok(file_name.to_string_lossy().to_string())
     }
 
+    // Queues a file that its source refuses to serve for the manual download flow
+    fn defer_to_manual_download(
+        &self,
+        state: &mut InstallerState,
+        error: anyhow::Error,
+        manual_download: impl FnOnce(&RestrictedFile) -> ManualDownload,
+    ) -> Result<()> {
+        let Some(restricted) = error.downcast_ref::<RestrictedFile>() else {
+            return Err(error);
+        };
+        // The file name comes from the API and is later joined to local directories
+        if !is_bare_file_name(&restricted.file_name) {
+            bail!(
+                "Invalid file name from CurseForge: {}",
+                restricted.file_name
+            );
+        }
+        let manual_download = manual_download(restricted);
+        if manual_download.hash.is_empty() && manual_download.published_sha1.is_none() {
+            bail!(
+                "{} cannot be verified because neither config.yaml nor CurseForge provides a SHA-1.",
+                manual_download.name
+            );
+        }
+        log::warn!("{restricted}");
+        state.add_manual_download(manual_download);
+        state.save(&self.state_path)?;
+        Ok(())
+    }
+
+    // Leaves the process resumable until the player has provided every manual download
+    fn ensure_no_manual_downloads(&self, state: &InstallerState) -> Result<()> {
+        let manual_downloads = state.get_manual_downloads();
+        if manual_downloads.is_empty() {
+            return Ok(());
+        }
+        emit_event(&self.app, InstallerEvent::RequireManualDownload);
+        bail!(
+            "{} files need to be downloaded manually: {}",
+            manual_downloads.len(),
+            manual_downloads
+                .iter()
+                .map(|m| m.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    // Looks up all Modrinth entries to be downloaded in a few requests
+    async fn resolve_modrinth_files(&mut self, state: &InstallerState) -> Result<()> {
+        let selection = state.get_selection();
+        let sources = self
+            .config
+            .get_mods()
+            .iter()
+            .filter(|entry| entry.should_install(&self.side, selection))
+            .filter(|entry| {
+                state
+                    .get_mod(entry)
+                    .map_or(true, |s| !s.equals(entry, false))
+            })
+            .map(|entry| &entry.source)
+            .chain(
+                self.config
+                    .get_resources()
+                    .iter()
+                    .filter(|entry| entry.should_install(&self.side, selection))
+                    .filter(|entry| state.get_resource(entry).map_or(true, |s| !s.equals(entry)))
+                    .map(|entry| &entry.source),
+            );
+        // Bundled files need no lookup
+        let bundle = self.bundle.as_ref();
+        let versions: Vec<(&str, &str)> = sources
+            .filter(|source| bundle.map_or(true, |bundle| !bundle.contains(&source.key())))
+            .filter_map(|source| match source {
+                SourceType::Modrinth {
+                    project_id,
+                    version_id,
+                } => Some((project_id.as_str(), version_id.as_str())),
+                _ => None,
+            })
+            .collect();
+        if versions.is_empty() {
+            return Ok(());
+        }
+        self.remote_files =
+            Modrinth::resolve_remote_files(&versions, &self.modrinth_cache_path).await?;
+        Ok(())
+    }
+
+    // Cross-checks the config hash with the one published by the source before downloading
+    async fn resolve_remote_file(
+        &self,
+        source: &SourceType,
+        name: &str,
+        expected_hash: &str,
+        expected_fingerprint: Option<u32>,
+    ) -> Result<RemoteFile> {
+        if let Some(remote_file) = self.find_in_bundle(&source.key(), expected_hash)? {
+            log::info!("Using bundled file for {name}.");
+            return cross_check_remote_file(remote_file, name, expected_hash, expected_fingerprint);
+        }
+        let remote_file = match source {
+            SourceType::Modrinth { version_id, .. }
+                if self.remote_files.contains_key(version_id) =>
+            {
+                self.remote_files[version_id].clone()
+            }
+            _ => {
+                source
+                    .get_remote_file(&self.curseforge, &self.allowlist, &self.config_dir)
+                    .await?
+            }
+        };
+        cross_check_remote_file(remote_file, name, expected_hash, expected_fingerprint)
+    }
+
+    fn find_in_bundle(&self, key: &str, expected_hash: &str) -> Result<Option<RemoteFile>> {
+        match &self.bundle {
+            Some(bundle) => bundle.get_remote_file(key, expected_hash),
+            None => Ok(None),
+        }
+    }
+
+    fn resolve_url_file(&self, url: &str, expected_hash: &str) -> Result<RemoteFile> {
+        Ok(self
+            .find_in_bundle(&Bundle::url_key(url), expected_hash)?
+            .unwrap_or_else(|| RemoteFile::from_url(url)))
+    }
+
     fn get_mods_dir(&self) -> PathBuf {
         self.install_dir.join("mods")
     }
 
+    // Returns the existing path of a mod file and whether the player disabled it
+    fn find_mod_file(&self, file_name: &str) -> Option<(PathBuf, bool)> {
+        let mod_path = self.get_mods_dir().join(file_name);
+        if mod_path.exists() {
+            return Some((mod_path, false));
+        }
+        let disabled_mod_path = disabled_path(&mod_path);
+        if disabled_mod_path.exists() {
+            return Some((disabled_mod_path, true));
+        }
+        None
+    }
+
+    // Warns about jars added by the player that share a mod ID with a tracked mod
+    fn check_duplicate_mods(&self, state: &InstallerState) {
+        log::info!("Checking for duplicate mods...");
+        let duplicates = match self.find_duplicate_mods(state) {
+            Ok(duplicates) => duplicates,
+            Err(e) => {
+                log::warn!("Failed to check for duplicate mods: {e:?}");
+                return;
+            }
+        };
+        for (mod_id, tracked_file, untracked_file) in duplicates {
+            log::warn!(
+                "Mod ID {mod_id} of untracked {untracked_file} collides with {tracked_file}."
+            );
+            emit_event(
+                &self.app,
+                InstallerEvent::AddAlert(AddAlertPayload {
+                    level: AlertLevel::Warning,
+                    translation_key: "alertOnDuplicateMod".to_string(),
+                    detail: Some(format!("{untracked_file} ({mod_id}) / {tracked_file}")),
+                    duplicate_mod: Some(untracked_file),
+                }),
+            );
+        }
+    }
+
+    // Warns about installed mods whose jars or Modrinth versions declare other
+    // Minecraft or loader versions
+    fn check_compatibility(&self, state: &InstallerState) {
+        let profile = self
+            .config
+            .get_variant_profile(state.get_selection().variant.as_deref());
+        let target = GameTarget::parse(&profile.version, self.config.get_mod_loader());
+        log::info!(
+            "Checking compatibility with {}: {target:?}",
+            profile.version
+        );
+        let modrinth_targets = Modrinth::get_cached_targets(&self.modrinth_cache_path);
+        let mut incompatible_mods = Vec::new();
+        for mod_state in state.get_all_mods() {
+            if !matches!(self.find_mod_file(&mod_state.file_name), Some((_, false))) {
+                continue;
+            }
+            let mut problems: Vec<String> = mod_state
+                .metadata
+                .iter()
+                .flat_map(|metadata| target.check_metadata(metadata))
+                .collect();
+            if let SourceType::Modrinth { version_id, .. } = &mod_state.source {
+                if let Some((game_versions, loaders)) = modrinth_targets.get(version_id) {
+                    problems.extend(target.check_modrinth(&mod_state.name, game_versions, loaders));
+                }
+            }
+            for problem in &problems {
+                log::warn!("Incompatible mod {}: {problem}", mod_state.file_name);
+            }
+            if !problems.is_empty() {
+                incompatible_mods.push(mod_state.name.clone());
+            }
+        }
+        if !incompatible_mods.is_empty() {
+            emit_event(
+                &self.app,
+                InstallerEvent::AddAlert(AddAlertPayload {
+                    level: AlertLevel::Warning,
+                    translation_key: "alertOnIncompatibleMods".to_string(),
+                    detail: Some(incompatible_mods.join(", ")),
+                    duplicate_mod: None,
+                }),
+            );
+        }
+    }
+
+    fn find_duplicate_mods(&self, state: &InstallerState) -> Result<Vec<(String, String, String)>> {
+        let mods_dir = self.get_mods_dir();
+        if !mods_dir.exists() {
+            return Ok(Vec::new());
+        }
+        // Disabled mods are not loaded by the game, so they cannot collide
+        let mut tracked_mod_ids = HashMap::new();
+        for mod_state in state.get_all_mods() {
+            if let Some((_, false)) = self.find_mod_file(&mod_state.file_name) {
+                for metadata in &mod_state.metadata {
+                    tracked_mod_ids.insert(metadata.mod_id.clone(), mod_state.file_name.clone());
+                }
+            }
+        }
+        let mut duplicates = Vec::new();
+        for entry in fs::read_dir(&mods_dir)? {
+            let path = entry?.path();
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if !path.is_file()
+                || !file_name.ends_with(".jar")
+                || state
+                    .get_all_mods()
+                    .iter()
+                    .any(|mod_state| mod_state.file_name == file_name)
+            {
+                continue;
+            }
+            for metadata in inspect_mod(&path) {
+                if let Some(tracked_file) = tracked_mod_ids.get(&metadata.mod_id) {
+                    duplicates.push((metadata.mod_id, tracked_file.clone(), file_name.to_string()));
+                }
+            }
+        }
+        Ok(duplicates)
+    }
+
     fn get_resource_dir(&self, entry: &ResourceEntry) -> PathBuf {
         self.install_dir.join(&entry.target_dir)
     }
@@ -431,7 +1349,7 @@ impl Installer {
     ) -> Result<()> {
         let mods_dir = self.get_mods_dir();
         for mod_entry in self.config.get_mods() {
-            if !mod_entry.should_install(&self.side) {
+            if !mod_entry.should_install(&self.side, state.get_selection()) {
                 continue;
             }
             let needs_download = state.get_mod(mod_entry).map_or(true, |downloaded_mod| {
@@ -450,10 +1368,47 @@ impl Installer {
                 }
             });
             if needs_download {
-                let url = mod_entry.source.get_download_url().await?;
+                let remote_file = match self
+                    .resolve_remote_file(
+                        &mod_entry.source,
+                        &mod_entry.name,
+                        &mod_entry.hash,
+                        mod_entry.fingerprint,
+                    )
+                    .await
+                {
+                    Ok(remote_file) => remote_file,
+                    Err(e) => {
+                        self.defer_to_manual_download(state, e, |restricted| ManualDownload {
+                            name: mod_entry.name.clone(),
+                            file_name: restricted.file_name.clone(),
+                            source: mod_entry.source.clone(),
+                            hash: mod_entry.hash.clone(),
+                            fingerprint: mod_entry.fingerprint,
+                            published_sha1: restricted.sha1.clone(),
+                            size: restricted.file_length,
+                            website_url: restricted.website_url.clone(),
+                            target_dir: None,
+                            decompress: false,
+                        })?;
+                        *completed_steps += 1u32;
+                        self.emit_progress(*completed_steps as f32 / total_steps as f32);
+                        continue;
+                    }
+                };
+                // Keep mods disabled by the player disabled after they are replaced
+                let mut is_disabled = state.is_pending_disabled_mod(mod_entry);
+                if let Some(downloaded_mod) = state.get_mod(mod_entry) {
+                    if let Some((old_path, true)) = self.find_mod_file(&downloaded_mod.file_name) {
+                        fs::remove_file(&old_path).with_context(|| {
+                            format!("Failed to remove mod file: {}", old_path.display())
+                        })?;
+                        is_disabled = true;
+                    }
+                }
                 let file_name = self
                     .ensure_download(
-                        &url,
+                        &remote_file,
                         &mod_entry.name,
                         &mod_entry.hash,
                         &mods_dir,
@@ -462,12 +1417,34 @@ impl Installer {
                         total_steps,
                     )
                     .await?;
+                let mod_path = mods_dir.join(&file_name);
+                let metadata = inspect_mod(&mod_path);
+                if is_disabled {
+                    move_file(&mod_path, &disabled_path(&mod_path))?;
+                    log::info!("Kept mod {} disabled.", mod_entry.name);
+                }
                 state.add_mod(ModState {
+                    name: mod_entry.name.clone(),
                     file_name,
                     source: mod_entry.source.clone(),
                     hash: mod_entry.hash.clone(),
+                    fingerprint: mod_entry.fingerprint,
+                    metadata,
                 });
                 state.save(&self.state_path)?;
+            } else if let Some(downloaded_mod) = state.get_mod(mod_entry) {
+                // Fill in metadata for mods recorded by older versions of the installer
+                if downloaded_mod.metadata.is_empty() {
+                    if let Some((mod_path, _)) = self.find_mod_file(&downloaded_mod.file_name) {
+                        let metadata = inspect_mod(&mod_path);
+                        if !metadata.is_empty() {
+                            let mut mod_state = downloaded_mod.clone();
+                            mod_state.metadata = metadata;
+                            state.add_mod(mod_state);
+                            state.save(&self.state_path)?;
+                        }
+                    }
+                }
             }
             *completed_steps += 1u32;
             self.emit_progress(*completed_steps as f32 / total_steps as f32);
@@ -482,7 +1459,7 @@ impl Installer {
         total_steps: u32,
     ) -> Result<()> {
         for resource_entry in self.config.get_resources() {
-            if !resource_entry.should_install(&self.side) {
+            if !resource_entry.should_install(&self.side, state.get_selection()) {
                 continue;
             }
             let needs_download =
@@ -503,12 +1480,42 @@ impl Installer {
                             false
                         }
                     });
+            let target_dir = self.get_resource_dir(resource_entry);
             if needs_download {
-                let url = resource_entry.source.get_download_url().await?;
-                let target_dir = self.get_resource_dir(resource_entry);
+                let remote_file = match self
+                    .resolve_remote_file(
+                        &resource_entry.source,
+                        &resource_entry.name,
+                        &resource_entry.hash,
+                        resource_entry.fingerprint,
+                    )
+                    .await
+                {
+                    Ok(remote_file) => remote_file,
+                    Err(e) => {
+                        self.defer_to_manual_download(state, e, |restricted| ManualDownload {
+                            name: resource_entry.name.clone(),
+                            file_name: restricted.file_name.clone(),
+                            source: resource_entry.source.clone(),
+                            hash: resource_entry.hash.clone(),
+                            fingerprint: resource_entry.fingerprint,
+                            published_sha1: restricted.sha1.clone(),
+                            size: restricted.file_length,
+                            website_url: restricted.website_url.clone(),
+                            target_dir: Some(resource_entry.target_dir.clone()),
+                            decompress: resource_entry.decompress,
+                        })?;
+                        *completed_steps += 1u32;
+                        self.emit_progress(*completed_steps as f32 / total_steps as f32);
+                        continue;
+                    }
+                };
+                if let Some(downloaded_resource) = state.get_resource(resource_entry) {
+                    self.remove_world_copies(downloaded_resource)?;
+                }
                 let file_name = self
                     .ensure_download(
-                        &url,
+                        &remote_file,
                         &resource_entry.name,
                         &resource_entry.hash,
                         &target_dir,
@@ -517,14 +1524,44 @@ impl Installer {
                         total_steps,
                     )
                     .await?;
+                let mut world_copies = Vec::new();
+                if let Some(world_target_dir) = &resource_entry.world_target_dir {
+                    self.place_world_copies(
+                        &target_dir.join(&file_name),
+                        world_target_dir,
+                        &mut world_copies,
+                    )?;
+                }
                 state.add_resource(ResourceState {
                     file_name,
                     source: resource_entry.source.clone(),
                     hash: resource_entry.hash.clone(),
+                    fingerprint: resource_entry.fingerprint,
                     target_dir: resource_entry.target_dir.clone(),
                     decompress: resource_entry.decompress,
+                    world_copies,
                 });
                 state.save(&self.state_path)?;
+            } else if let Some(downloaded_resource) = state.get_resource(resource_entry) {
+                // Worlds created since the last run also need a copy
+                let mut resource_state = downloaded_resource.clone();
+                let is_changed = match &resource_entry.world_target_dir {
+                    Some(world_target_dir) => self.place_world_copies(
+                        &target_dir.join(&resource_state.file_name),
+                        world_target_dir,
+                        &mut resource_state.world_copies,
+                    )?,
+                    None if !resource_state.world_copies.is_empty() => {
+                        self.remove_world_copies(&resource_state)?;
+                        resource_state.world_copies.clear();
+                        true
+                    }
+                    None => false,
+                };
+                if is_changed {
+                    state.add_resource(resource_state);
+                    state.save(&self.state_path)?;
+                }
             }
             *completed_steps += 1u32;
             self.emit_progress(*completed_steps as f32 / total_steps as f32);
@@ -532,6 +1569,102 @@ impl Installer {
         Ok(())
     }
 
+    fn get_world_dirs(&self) -> Result<Vec<PathBuf>> {
+        let saves_dir = self.install_dir.join("saves");
+        if !saves_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut world_dirs: Vec<PathBuf> = fs::read_dir(&saves_dir)
+            .with_context(|| format!("Failed to read saves directory {}", saves_dir.display()))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.join("level.dat").is_file())
+            .collect();
+        world_dirs.sort();
+        Ok(world_dirs)
+    }
+
+    // Brings the tracked copies in line with the current worlds and target
+    // directory: copies that no longer apply are removed, missing or stale ones
+    // are (re)placed. Files the installer did not place are never overwritten.
+    fn place_world_copies(
+        &self,
+        source_path: &Path,
+        world_target_dir: &str,
+        world_copies: &mut Vec<String>,
+    ) -> Result<bool> {
+        let file_name = source_path
+            .file_name()
+            .ok_or_else(|| anyhow!("Invalid resource path: {}", source_path.display()))?;
+        let source_hash = hash_file(source_path)?;
+        let mut is_changed = false;
+        let mut placed_copies = Vec::new();
+        for world_dir in self.get_world_dirs()? {
+            let destination = world_dir.join(world_target_dir).join(file_name);
+            let relative_path = destination
+                .strip_prefix(&self.install_dir)
+                .unwrap_or(&destination)
+                .to_string_lossy()
+                .to_string();
+            let is_tracked = world_copies.contains(&relative_path);
+            if !is_tracked && destination.exists() {
+                log::warn!(
+                    "Not placing a world copy over an existing file: {}",
+                    destination.display()
+                );
+                continue;
+            }
+            placed_copies.push(relative_path);
+            let is_current = is_tracked
+                && destination.is_file()
+                && hash_file(&destination).is_ok_and(|hash| hash == source_hash);
+            if is_current {
+                continue;
+            }
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+            }
+            fs::copy(source_path, &destination).with_context(|| {
+                format!(
+                    "Failed to copy {} to {}",
+                    source_path.display(),
+                    destination.display()
+                )
+            })?;
+            log::info!("Placed world copy: {}", destination.display());
+            is_changed = true;
+        }
+        for world_copy in world_copies.iter() {
+            if placed_copies.contains(world_copy) {
+                continue;
+            }
+            let path = self.install_dir.join(world_copy);
+            if path.exists() {
+                log::info!("Removing outdated world copy: {}", path.display());
+                fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove world copy: {}", path.display()))?;
+            }
+            is_changed = true;
+        }
+        *world_copies = placed_copies;
+        Ok(is_changed)
+    }
+
+    fn remove_world_copies(&self, resource_state: &ResourceState) -> Result<()> {
+        for world_copy in &resource_state.world_copies {
+            let path = self.install_dir.join(world_copy);
+            if path.exists() {
+                log::info!("Removing world copy: {}", path.display());
+                fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove world copy: {}", path.display()))?;
+            } else {
+                log::warn!("World copy to remove does not exist: {}", path.display());
+            }
+        }
+        Ok(())
+    }
+
     async fn update_settings(
         &self,
         state: &mut InstallerState,
@@ -544,11 +1677,10 @@ impl Installer {
         if now < &v1_2_0 && new >= &v1_2_0 {
             // v1.2.0 update
             log::info!("Updating config files for v1.2.0...");
-            let url = "https://github.com/kyazuki/Makibania-Modpack-Resources/releases/download/v1.2.0/configs.zip";
             self.ensure_download(
-                url,
+                &self.resolve_url_file(CONFIGS_V1_2_0.url, CONFIGS_V1_2_0.hash)?,
                 "configs",
-                "4cb14e94845a0f03775c0d1b8f3f0cbddb675ddb",
+                CONFIGS_V1_2_0.hash,
                 &self.install_dir.join("config"),
                 true,
                 *completed_steps,
@@ -562,11 +1694,10 @@ impl Installer {
         if now < &v1_2_1 && new >= &v1_2_1 {
             // v1.2.1 update
             log::info!("Updating config files for v1.2.1...");
-            let url = "https://github.com/kyazuki/Makibania-Modpack-Resources/releases/download/v1.2.1/configs.zip";
             self.ensure_download(
-                url,
+                &self.resolve_url_file(CONFIGS_V1_2_1.url, CONFIGS_V1_2_1.hash)?,
                 "configs",
-                "9e5f63a8b1a6da42792ffc1563dcd6c6f6eac495",
+                CONFIGS_V1_2_1.hash,
                 &self.install_dir.join("config"),
                 true,
                 *completed_steps,
@@ -752,41 +1883,22 @@ impl Installer {
             InstallerEvent::AddAlert(AddAlertPayload {
                 level,
                 translation_key: translation_key.to_string(),
+                detail: None,
+                duplicate_mod: None,
             }),
         );
     }
 
-    fn add_launcher_profile(&self) -> Result<()> {
+    fn add_launcher_profile(&self, profile: &Profile) -> Result<()> {
         log::info!("Adding launcher profile...");
-        let profiles_path = if cfg!(target_os = "windows") {
-            let appdata = env::var("APPDATA").context("APPDATA environment variable not found")?;
-            PathBuf::from(appdata)
-                .join(".minecraft")
-                .join("launcher_profiles.json")
-        } else if cfg!(target_os = "macos") {
-            let home = env::var("HOME").context("HOME environment variable not found")?;
-            PathBuf::from(home)
-                .join("Library")
-                .join("Application Support")
-                .join("minecraft")
-                .join("launcher_profiles.json")
-        } else {
-            bail!("Unsupported operating system: {}", env::consts::OS);
-        };
-        if !profiles_path.exists() {
-            bail!("Launcher profiles file not found. ");
-        }
-        // Load existing profiles
-        let content =
-            fs::read_to_string(&profiles_path).context("Failed to read launcher_profiles.json")?;
-        let mut launcher_profiles: LauncherProfiles =
-            serde_json::from_str(&content).context("Failed to parse launcher_profiles.json")?;
+        let profiles_path = get_launcher_profiles_path()?;
+        let mut launcher_profiles = load_launcher_profiles(&profiles_path)?;
         // Check if profile already exists
-        for profile in launcher_profiles.profiles.values() {
-            if profile.name == self.config.get_profile().name {
+        for launcher_profile in launcher_profiles.profiles.values() {
+            if launcher_profile.name == profile.name {
                 log::info!(
                     "Launcher profile '{}' already exists, skipping addition.",
-                    profile.name
+                    launcher_profile.name
                 );
                 return Ok(());
             }
@@ -799,12 +1911,12 @@ impl Installer {
         let new_profile = LauncherProfile {
             created: Some(now_rounded),
             game_dir: Some(self.install_dir.clone()),
-            icon: self.config.get_profile().icon.clone(),
-            java_args: self.config.get_profile().jvm_args.clone(),
+            icon: profile.icon.clone(),
+            java_args: profile.jvm_args.clone(),
             java_dir: None,
             last_used: Some(now_rounded),
-            last_version_id: self.config.get_profile().version.clone(),
-            name: self.config.get_profile().name.clone(),
+            last_version_id: profile.version.clone(),
+            name: profile.name.clone(),
             resolution: None,
             skip_jre_version_check: None,
             profile_type: "custom".to_string(),
@@ -816,28 +1928,26 @@ impl Installer {
         {
             bail!("Profile ID '{profile_id}' already exists in launcher profiles");
         }
-        // Backup original file
-        let mut backup_path = profiles_path.with_extension("json.bak");
-        let mut backup_index = 1;
-        while backup_path.exists() {
-            backup_path = profiles_path.with_extension(format!("json.bak{backup_index}"));
-            backup_index += 1;
-        }
-        fs::rename(&profiles_path, &backup_path)
-            .context("Failed to backup launcher_profiles.json")?;
-        log::info!(
-            "Backed up launcher_profiles.json to {}",
-            backup_path.display()
-        );
-        // Save profiles
-        let profiles_json = serde_json::to_string_pretty(&launcher_profiles)
-            .context("Failed to serialize profiles")?;
-        fs::write(&profiles_path, profiles_json)
-            .context("Failed to write launcher_profiles.json")?;
-        log::info!(
-            "Added profile '{}' to launcher.",
-            self.config.get_profile().name
-        );
+        save_launcher_profiles(&profiles_path, &launcher_profiles)?;
+        log::info!("Added profile '{}' to launcher.", profile.name);
+
+        Ok(())
+    }
+
+    fn update_launcher_profile(&self, profile: &Profile) -> Result<()> {
+        log::info!("Updating launcher profile...");
+        let profiles_path = get_launcher_profiles_path()?;
+        let mut launcher_profiles = load_launcher_profiles(&profiles_path)?;
+        let launcher_profile = launcher_profiles
+            .profiles
+            .values_mut()
+            .find(|launcher_profile| launcher_profile.name == profile.name)
+            .ok_or_else(|| anyhow!("Launcher profile '{}' is not found", profile.name))?;
+        launcher_profile.icon = profile.icon.clone();
+        launcher_profile.java_args = profile.jvm_args.clone();
+        launcher_profile.last_version_id = profile.version.clone();
+        save_launcher_profiles(&profiles_path, &launcher_profiles)?;
+        log::info!("Updated profile '{}' in launcher.", profile.name);
 
         Ok(())
     }
@@ -888,6 +1998,57 @@ impl Installer {
     }
 }
 
+fn get_launcher_profiles_path() -> Result<PathBuf> {
+    let profiles_path = if cfg!(target_os = "windows") {
+        let appdata = env::var("APPDATA").context("APPDATA environment variable not found")?;
+        PathBuf::from(appdata)
+            .join(".minecraft")
+            .join("launcher_profiles.json")
+    } else if cfg!(target_os = "macos") {
+        let home = env::var("HOME").context("HOME environment variable not found")?;
+        PathBuf::from(home)
+            .join("Library")
+            .join("Application Support")
+            .join("minecraft")
+            .join("launcher_profiles.json")
+    } else {
+        bail!("Unsupported operating system: {}", env::consts::OS);
+    };
+    if !profiles_path.exists() {
+        bail!("Launcher profiles file not found. ");
+    }
+    Ok(profiles_path)
+}
+
+fn load_launcher_profiles(profiles_path: &Path) -> Result<LauncherProfiles> {
+    let content =
+        fs::read_to_string(profiles_path).context("Failed to read launcher_profiles.json")?;
+    serde_json::from_str(&content).context("Failed to parse launcher_profiles.json")
+}
+
+fn save_launcher_profiles(
+    profiles_path: &Path,
+    launcher_profiles: &LauncherProfiles,
+) -> Result<()> {
+    // Backup original file
+    let mut backup_path = profiles_path.with_extension("json.bak");
+    let mut backup_index = 1;
+    while backup_path.exists() {
+        backup_path = profiles_path.with_extension(format!("json.bak{backup_index}"));
+        backup_index += 1;
+    }
+    fs::rename(profiles_path, &backup_path).context("Failed to backup launcher_profiles.json")?;
+    log::info!(
+        "Backed up launcher_profiles.json to {}",
+        backup_path.display()
+    );
+    // Save profiles
+    let profiles_json =
+        serde_json::to_string_pretty(launcher_profiles).context("Failed to serialize profiles")?;
+    fs::write(profiles_path, profiles_json).context("Failed to write launcher_profiles.json")?;
+    Ok(())
+}
+
 fn find_java() -> Option<PathBuf> {
     // 1. Check system java command
     log::info!("Searching for system java...");
@@ -995,6 +2156,7 @@ enum InstallerEvent {
     ChangeDetail(ChangeDetailPayload),
     UpdateProgress(UpdateProgressPayload),
     AddAlert(AddAlertPayload),
+    RequireManualDownload,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -1028,6 +2190,11 @@ struct UpdateProgressPayload {
 struct AddAlertPayload {
     level: AlertLevel,
     translation_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+    // File name of an untracked mod that can be quarantined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicate_mod: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -1041,6 +2208,81 @@ fn hash_matches(expected: &str, actual: &str) -> bool {
     expected.eq_ignore_ascii_case(actual)
 }
 
+// Cross-checks the config checksums with the ones published by the download source
+fn cross_check_remote_file(
+    mut remote_file: RemoteFile,
+    name: &str,
+    expected_hash: &str,
+    expected_fingerprint: Option<u32>,
+) -> Result<RemoteFile> {
+    if let Some(sha1) = remote_file
+        .sha1
+        .as_ref()
+        .filter(|_| !expected_hash.is_empty())
+    {
+        if !hash_matches(expected_hash, sha1) {
+            bail!(
+                "The hash of {name} in config.yaml ({expected_hash}) does not match the SHA-1 \
+                 published by the download source ({sha1}). The modpack config needs to be fixed."
+            );
+        }
+    }
+    if let Some(expected_fingerprint) = expected_fingerprint {
+        if let Some(fingerprint) = remote_file.fingerprint {
+            if fingerprint != expected_fingerprint {
+                bail!(
+                    "The fingerprint of {name} in config.yaml ({expected_fingerprint}) does not \
+                     match the one published by CurseForge ({fingerprint}). The modpack config \
+                     needs to be fixed."
+                );
+            }
+        }
+        remote_file.fingerprint = Some(expected_fingerprint);
+    }
+    // A fingerprint alone is a 32-bit hash and does not identify the file
+    let is_verifiable =
+        !expected_hash.is_empty() || remote_file.sha1.is_some() || remote_file.sha256.is_some();
+    if !is_verifiable {
+        bail!(
+            "{name} cannot be verified because neither config.yaml nor the download source \
+             provides a SHA-1 or SHA-256."
+        );
+    }
+    Ok(remote_file)
+}
+
+fn verify_download(
+    remote_file: &RemoteFile,
+    expected_hash: &str,
+    outcome: &DownloadOutcome,
+) -> Result<()> {
+    // Entries without a hash use the SHA-1 published by the source
+    match remote_file.sha1.as_deref() {
+        _ if !expected_hash.is_empty() => verify_hash(expected_hash, &outcome.hash, &outcome.path)?,
+        Some(sha1) => verify_hash(sha1, &outcome.hash, &outcome.path)?,
+        None => {}
+    }
+    if let Some(fingerprint) = remote_file.fingerprint {
+        verify_fingerprint(fingerprint, &outcome.path)?;
+    }
+    if let Some(sha256) = &remote_file.sha256 {
+        verify_hash(sha256, &outcome.sha256, &outcome.path)?;
+    }
+    if let Some(sha512) = &remote_file.sha512 {
+        verify_hash(sha512, &outcome.sha512, &outcome.path)?;
+    }
+    if let Some(size) = remote_file.size {
+        if size != outcome.size {
+            bail!(
+                "Size mismatch for {}. Expected {size} bytes, got {} bytes",
+                outcome.path.display(),
+                outcome.size
+            );
+        }
+    }
+    Ok(())
+}
+
 fn verify_hash(expected: &str, actual: &str, final_path: &Path) -> Result<()> {
     if hash_matches(expected, actual) {
         Ok(())
@@ -1052,12 +2294,63 @@ fn verify_hash(expected: &str, actual: &str, final_path: &Path) -> Result<()> {
     }
 }
 
+fn verify_fingerprint(expected: u32, path: &Path) -> Result<()> {
+    let actual = curseforge::fingerprint_file(path)?;
+    if actual != expected {
+        bail!(
+            "Fingerprint mismatch for {}. Expected {expected}, got {actual}",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
 fn emit_event(app: &AppHandle, payload: InstallerEvent) {
     if let Err(e) = app.emit("installer://event", &payload) {
         log::warn!("Failed to emit installer event. payload: {payload:?}, error: {e:?}");
     }
 }
 
+fn inspect_mod(path: &Path) -> Vec<ModMetadata> {
+    match jar::inspect_jar(path) {
+        Ok(metadata) => {
+            for entry in &metadata {
+                log::info!(
+                    "Found mod metadata: {} {} (Minecraft: {}, loader: {})",
+                    entry.mod_id,
+                    entry.version.as_deref().unwrap_or("unknown"),
+                    entry.minecraft_version_range.as_deref().unwrap_or("any"),
+                    entry.loader_version_range.as_deref().unwrap_or("any"),
+                );
+            }
+            metadata
+        }
+        Err(e) => {
+            log::warn!(
+                "Failed to read mod metadata from {}: {:?}",
+                path.display(),
+                e
+            );
+            Vec::new()
+        }
+    }
+}
+
+fn hash_file(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha1::new();
+    io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn disabled_path(path: &Path) -> PathBuf {
+    let mut disabled_path = path.as_os_str().to_owned();
+    disabled_path.push(".disabled");
+    PathBuf::from(disabled_path)
+}
+
 fn move_file(source: &Path, destination: &Path) -> Result<()> {
     if let Some(parent) = destination.parent() {
         fs::create_dir_all(parent).with_context(|| {
@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+const NEOFORGE_METADATA_PATH: &str = "META-INF/neoforge.mods.toml";
+const FORGE_METADATA_PATH: &str = "META-INF/mods.toml";
+const FABRIC_METADATA_PATH: &str = "fabric.mod.json";
+const MANIFEST_PATH: &str = "META-INF/MANIFEST.MF";
+
+const MINECRAFT_MOD_ID: &str = "minecraft";
+const LOADER_MOD_IDS: [&str; 3] = ["neoforge", "forge", "fabricloader"];
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MetadataFormat {
+    Neoforge,
+    Forge,
+    Fabric,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModMetadata {
+    pub format: MetadataFormat,
+    pub mod_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<ModDependency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minecraft_version_range: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loader_version_range: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModDependency {
+    pub mod_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_range: Option<String>,
+    pub required: bool,
+}
+
+// Reads loader metadata of every mod declared in a jar.
+// Returns an empty list for jars without known metadata files (e.g. libraries).
+pub fn inspect_jar(path: &Path) -> Result<Vec<ModMetadata>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open mod file: {}", path.display()))?;
+    let mut archive = ZipArchive::new(file)
+        .with_context(|| format!("Failed to read mod file as zip: {}", path.display()))?;
+
+    if let Some(content) = read_entry(&mut archive, NEOFORGE_METADATA_PATH)? {
+        let jar_version = read_jar_version(&mut archive)?;
+        return parse_mods_toml(&content, MetadataFormat::Neoforge, jar_version.as_deref())
+            .with_context(|| format!("Failed to parse {NEOFORGE_METADATA_PATH}"));
+    }
+    if let Some(content) = read_entry(&mut archive, FORGE_METADATA_PATH)? {
+        let jar_version = read_jar_version(&mut archive)?;
+        return parse_mods_toml(&content, MetadataFormat::Forge, jar_version.as_deref())
+            .with_context(|| format!("Failed to parse {FORGE_METADATA_PATH}"));
+    }
+    if let Some(content) = read_entry(&mut archive, FABRIC_METADATA_PATH)? {
+        return parse_fabric_mod_json(&content)
+            .with_context(|| format!("Failed to parse {FABRIC_METADATA_PATH}"));
+    }
+    Ok(Vec::new())
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Option<String>> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes)?;
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+// Forge and NeoForge mods usually declare `version="${file.jarVersion}"`,
+// which is resolved from Implementation-Version in the manifest.
+fn read_jar_version(archive: &mut ZipArchive<File>) -> Result<Option<String>> {
+    Ok(read_entry(archive, MANIFEST_PATH)?.and_then(|manifest| {
+        manifest.lines().find_map(|line| {
+            line.strip_prefix("Implementation-Version:")
+                .map(|version| version.trim().to_string())
+        })
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModsToml {
+    #[serde(default)]
+    mods: Vec<ModsTomlMod>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, Vec<ModsTomlDependency>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModsTomlMod {
+    mod_id: String,
+    version: Option<String>,
+    display_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModsTomlDependency {
+    mod_id: String,
+    version_range: Option<String>,
+    // Forge
+    mandatory: Option<bool>,
+    // NeoForge
+    #[serde(rename = "type")]
+    dependency_type: Option<String>,
+}
+
+fn parse_mods_toml(
+    content: &str,
+    format: MetadataFormat,
+    jar_version: Option<&str>,
+) -> Result<Vec<ModMetadata>> {
+    let mut mods_toml: ModsToml = toml::from_str(content)?;
+    Ok(mods_toml
+        .mods
+        .into_iter()
+        .map(|entry| {
+            let dependencies: Vec<ModDependency> = mods_toml
+                .dependencies
+                .remove(&entry.mod_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|dependency| ModDependency {
+                    required: dependency.mandatory.unwrap_or_else(|| {
+                        dependency
+                            .dependency_type
+                            .as_deref()
+                            .map_or(true, |t| t.eq_ignore_ascii_case("required"))
+                    }),
+                    mod_id: dependency.mod_id,
+                    version_range: dependency.version_range,
+                })
+                .collect();
+            let version = match (entry.version, jar_version) {
+                (Some(version), Some(jar_version)) if version == "${file.jarVersion}" => {
+                    Some(jar_version.to_string())
+                }
+                (version, _) => version,
+            };
+            ModMetadata {
+                format,
+                minecraft_version_range: find_version_range(&dependencies, &[MINECRAFT_MOD_ID]),
+                // `loaderVersion` is the language provider version, not the loader version
+                loader_version_range: find_version_range(&dependencies, &LOADER_MOD_IDS),
+                mod_id: entry.mod_id,
+                version,
+                display_name: entry.display_name,
+                dependencies,
+            }
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct FabricModJson {
+    id: String,
+    version: Option<String>,
+    name: Option<String>,
+    #[serde(default)]
+    depends: BTreeMap<String, FabricVersionRange>,
+    #[serde(default)]
+    recommends: BTreeMap<String, FabricVersionRange>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FabricVersionRange {
+    Single(String),
+    Any(Vec<String>),
+}
+
+impl FabricVersionRange {
+    fn to_range(&self) -> String {
+        match self {
+            FabricVersionRange::Single(range) => range.clone(),
+            FabricVersionRange::Any(ranges) => ranges.join(" || "),
+        }
+    }
+}
+
+fn parse_fabric_mod_json(content: &str) -> Result<Vec<ModMetadata>> {
+    let mod_json: FabricModJson = serde_json::from_str(content)?;
+    let dependencies: Vec<ModDependency> = mod_json
+        .depends
+        .iter()
+        .map(|(mod_id, range)| (mod_id, range, true))
+        .chain(
+            mod_json
+                .recommends
+                .iter()
+                .map(|(mod_id, range)| (mod_id, range, false)),
+        )
+        .map(|(mod_id, range, required)| ModDependency {
+            mod_id: mod_id.clone(),
+            version_range: Some(range.to_range()),
+            required,
+        })
+        .collect();
+    Ok(vec![ModMetadata {
+        format: MetadataFormat::Fabric,
+        minecraft_version_range: find_version_range(&dependencies, &[MINECRAFT_MOD_ID]),
+        loader_version_range: find_version_range(&dependencies, &LOADER_MOD_IDS),
+        mod_id: mod_json.id,
+        version: mod_json.version,
+        display_name: mod_json.name,
+        dependencies,
+    }])
+}
+
+fn find_version_range(dependencies: &[ModDependency], mod_ids: &[&str]) -> Option<String> {
+    dependencies
+        .iter()
+        .find(|dependency| mod_ids.contains(&dependency.mod_id.as_str()))
+        .and_then(|dependency| dependency.version_range.clone())
+}
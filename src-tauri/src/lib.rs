@@ -1,18 +1,43 @@
+mod bundle;
+mod cache;
+mod compat;
 mod config;
+mod curseforge;
 mod downloader;
+mod github;
 mod installer;
+mod jar;
 mod launcher;
+mod maven;
 mod modrinth;
+mod network;
+mod peer;
+mod remote;
+mod signature;
 mod state;
 
-use std::{env, path::PathBuf, sync::Mutex};
+use std::{
+    collections::HashMap,
+    env,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
 
 use serde::Serialize;
 use tauri::Manager;
+use tauri_plugin_dialog::DialogExt;
 use tauri_plugin_opener::OpenerExt;
 
-use crate::config::Side;
+use crate::cache::{CacheUsage, DownloadCache};
+use crate::config::{ModPackConfig, Selection, Side};
+use crate::curseforge::ApiKey;
 use crate::installer::{Installer, InstallerMode};
+use crate::maven::Maven;
+use crate::modrinth::{DependencyIssue, Modrinth};
+use crate::peer::CacheServer;
+use crate::remote::RemoteConfig;
+use crate::state::InstallerState;
 
 pub struct AppState {
     config_path: PathBuf,
@@ -20,7 +45,12 @@ pub struct AppState {
     app_dir: PathBuf,
     state_path: PathBuf,
     log_dir: PathBuf,
+    side: Side,
     is_running: Mutex<bool>,
+    selection: Mutex<Option<Selection>>,
+    curseforge_api_key: Mutex<Option<Arc<ApiKey>>>,
+    cache_server: Mutex<Option<CacheServer>>,
+    cache_peer: Mutex<Option<String>>,
 }
 
 #[derive(Serialize)]
@@ -28,6 +58,8 @@ pub struct AppState {
 pub struct TitleStatus {
     pub can_install: bool,
     pub can_update: bool,
+    pub can_change_options: bool,
+    pub disabled_mods: Vec<String>,
 }
 
 #[tauri::command]
@@ -37,10 +69,207 @@ fn initialize_title(state: tauri::State<AppState>) -> TitleStatus {
         can_install: Installer::can_install(&state.config_path, &state.state_path)
             .inspect_err(|e| log::warn!("Disabled install mode: {:?}", e))
             .is_ok(),
-        can_update: Installer::can_update(&state.config_path, &state.state_path)
+        can_update: Installer::can_update(&state.config_path, &state.state_path, None)
             .inspect_err(|e| log::warn!("Disabled update mode: {:?}", e))
             .is_ok(),
+        can_change_options: Installer::can_change_options(&state.config_path, &state.state_path)
+            .inspect_err(|e| log::info!("Disabled changing options: {:?}", e))
+            .is_ok(),
+        disabled_mods: Installer::get_disabled_mods(&state.install_dir, &state.state_path)
+            .inspect(|mods| {
+                if !mods.is_empty() {
+                    log::info!("Disabled mods: {}", mods.join(", "));
+                }
+            })
+            .inspect_err(|e| log::warn!("Failed to check disabled mods: {:?}", e))
+            .unwrap_or_default(),
+    }
+}
+
+// Downloads a newer config.yaml from the pack's update URL before the title
+// status is computed. Returns the new pack version.
+#[tauri::command]
+async fn fetch_remote_config(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    log::info!("Called fetch_remote_config.");
+    let state = app.state::<AppState>();
+    {
+        let mut is_running = state.is_running.lock().unwrap();
+        if *is_running {
+            return Err("Installer is already running".to_string());
+        }
+        *is_running = true;
+    }
+    let result = RemoteConfig::update(&state.config_path, &state.state_path, &state.app_dir)
+        .await
+        .map(|version| version.map(|version| version.to_string()))
+        .map_err(|e| {
+            log::error!("Failed to fetch remote config: {e:?}");
+            format!("{e}")
+        });
+    *state.is_running.lock().unwrap() = false;
+    result
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelInfo {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelsInfo {
+    pub channels: Vec<ChannelInfo>,
+    pub selected: Option<String>,
+}
+
+// Channels can be switched once the pack is installed
+#[tauri::command]
+fn get_channels(state: tauri::State<AppState>) -> Result<ChannelsInfo, String> {
+    log::info!("Called get_channels.");
+    if !state.state_path.exists() {
+        return Ok(ChannelsInfo {
+            channels: Vec::new(),
+            selected: None,
+        });
     }
+    let config = ModPackConfig::load_from_path(&state.config_path).map_err(|e| {
+        log::error!("Failed to load config: {e:?}");
+        format!("{e}")
+    })?;
+    let installer_state = InstallerState::load(&state.state_path).map_err(|e| {
+        log::error!("Failed to load installer state: {e:?}");
+        format!("{e}")
+    })?;
+    let channels = config.get_channels();
+    Ok(ChannelsInfo {
+        channels: channels
+            .iter()
+            .map(|channel| ChannelInfo {
+                id: channel.id.clone(),
+                name: channel.name.clone(),
+            })
+            .collect(),
+        selected: installer_state
+            .get_channel()
+            .filter(|id| channels.iter().any(|channel| channel.id == *id))
+            .or_else(|| channels.first().map(|channel| channel.id.as_str()))
+            .map(str::to_string),
+    })
+}
+
+#[tauri::command]
+async fn select_channel(app: tauri::AppHandle, channel: String) -> Result<Option<String>, String> {
+    log::info!("Called select_channel: {channel}");
+    let state = app.state::<AppState>();
+    {
+        let mut is_running = state.is_running.lock().unwrap();
+        if *is_running {
+            return Err("Installer is already running".to_string());
+        }
+        *is_running = true;
+    }
+    let result = RemoteConfig::switch_channel(
+        &state.config_path,
+        &state.state_path,
+        &state.app_dir,
+        &channel,
+    )
+    .await
+    .map(|version| version.map(|version| version.to_string()))
+    .map_err(|e| {
+        log::error!("Failed to switch channel: {e:?}");
+        format!("{e}")
+    });
+    *state.is_running.lock().unwrap() = false;
+    result
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantInfo {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionGroupInfo {
+    pub id: String,
+    pub name: String,
+    pub pick_one: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionChoice {
+    pub name: String,
+    pub group: Option<String>,
+    pub default: bool,
+    pub selected: bool,
+    pub variants: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionsInfo {
+    pub variants: Vec<VariantInfo>,
+    pub selected_variant: Option<String>,
+    pub groups: Vec<OptionGroupInfo>,
+    pub choices: Vec<OptionChoice>,
+}
+
+#[tauri::command]
+fn get_options(state: tauri::State<AppState>) -> Result<OptionsInfo, String> {
+    log::info!("Called get_options.");
+    let config = ModPackConfig::load_from_path(&state.config_path).map_err(|e| {
+        log::error!("Failed to load config: {e:?}");
+        format!("{e}")
+    })?;
+    let selection =
+        resolve_saved_selection(&config, &state.state_path).map_err(|e| format!("{e}"))?;
+    let side = state.side;
+    let mod_choices = config
+        .get_mods()
+        .iter()
+        .filter(|entry| entry.optional && entry.is_for_side(&side) && entry.is_for_platform())
+        .map(|entry| (&entry.name, &entry.group, entry.default, &entry.variants));
+    let resource_choices = config
+        .get_resources()
+        .iter()
+        .filter(|entry| entry.optional && entry.is_for_side(&side) && entry.is_for_platform())
+        .map(|entry| (&entry.name, &entry.group, entry.default, &entry.variants));
+    Ok(OptionsInfo {
+        variants: config
+            .get_variants()
+            .iter()
+            .map(|variant| VariantInfo {
+                id: variant.id.clone(),
+                name: variant.name.clone(),
+            })
+            .collect(),
+        selected_variant: selection.variant.clone(),
+        groups: config
+            .get_option_groups()
+            .iter()
+            .map(|group| OptionGroupInfo {
+                id: group.id.clone(),
+                name: group.name.clone(),
+                pick_one: group.pick_one,
+            })
+            .collect(),
+        choices: mod_choices
+            .chain(resource_choices)
+            .map(|(name, group, default, variants)| OptionChoice {
+                name: name.clone(),
+                group: group.clone(),
+                default,
+                selected: selection.options.get(name).copied().unwrap_or(default),
+                variants: variants.clone(),
+            })
+            .collect(),
+    })
 }
 
 #[derive(Serialize)]
@@ -51,12 +280,19 @@ pub struct ModeResult {
 }
 
 #[tauri::command]
-fn select_mode(state: tauri::State<AppState>, mode: InstallerMode) -> ModeResult {
+fn select_mode(
+    state: tauri::State<AppState>,
+    mode: InstallerMode,
+    selection: Option<Selection>,
+) -> ModeResult {
     log::info!("Selected mode: {mode:?}");
     let result = match mode {
         InstallerMode::Install => Installer::can_install(&state.config_path, &state.state_path),
-        InstallerMode::Update => Installer::can_update(&state.config_path, &state.state_path),
+        InstallerMode::Update => {
+            Installer::can_update(&state.config_path, &state.state_path, selection.as_ref())
+        }
     };
+    *state.selection.lock().unwrap() = selection;
     if let Err(ref err) = result {
         log::error!("Failed to start {mode:?}: {err:?}");
     }
@@ -77,6 +313,330 @@ fn open_log_folder(app: tauri::AppHandle) -> () {
     }
 }
 
+// Hidden checks for pack authors
+#[tauri::command]
+async fn validate_config(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    log::info!("Called validate_config.");
+    let state = app.state::<AppState>();
+    // Shared so that dependencies are checked without fetching versions again
+    let mut modrinth_versions = HashMap::new();
+    let mut errors = Installer::validate_compatibility(
+        &state.config_path,
+        &state.state_path,
+        &mut modrinth_versions,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("Failed to validate config: {e:?}");
+        format!("{e}")
+    })?;
+    errors.extend(
+        check_config_dependencies(&state, &mut modrinth_versions)
+            .await?
+            .iter()
+            .map(DependencyIssue::to_string),
+    );
+    for error in &errors {
+        log::warn!("Validation error: {error}");
+    }
+    Ok(errors)
+}
+
+#[tauri::command]
+async fn check_dependencies(app: tauri::AppHandle) -> Result<Vec<DependencyIssue>, String> {
+    log::info!("Called check_dependencies.");
+    let state = app.state::<AppState>();
+    check_config_dependencies(&state, &mut HashMap::new()).await
+}
+
+// Checks the mods installed for the saved selection
+async fn check_config_dependencies(
+    state: &AppState,
+    modrinth_versions: &mut HashMap<String, modrinth::Version>,
+) -> Result<Vec<DependencyIssue>, String> {
+    let config = ModPackConfig::load_from_path(&state.config_path).map_err(|e| {
+        log::error!("Failed to load config: {e:?}");
+        format!("{e}")
+    })?;
+    let selection =
+        resolve_saved_selection(&config, &state.state_path).map_err(|e| format!("{e}"))?;
+    Modrinth::check_dependencies(&config, &selection, &state.side, modrinth_versions)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to check dependencies: {e:?}");
+            format!("{e}")
+        })
+}
+
+// Falls back to the defaults when the saved selection is no longer valid
+fn resolve_saved_selection(config: &ModPackConfig, state_path: &Path) -> anyhow::Result<Selection> {
+    let saved_selection = InstallerState::load(state_path)
+        .map(|s| s.get_selection().clone())
+        .unwrap_or_default();
+    config
+        .resolve_selection(&saved_selection)
+        .or_else(|_| config.resolve_selection(&Selection::default()))
+}
+
+// Authoring helper: the newest release of `group:artifact` for a Minecraft version
+#[tauri::command]
+async fn resolve_maven_version(
+    repository: String,
+    artifact: String,
+    minecraft_version: String,
+) -> Result<Option<String>, String> {
+    log::info!("Called resolve_maven_version.");
+    Maven::resolve_latest_version(&repository, &artifact, &minecraft_version)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to resolve Maven version: {e:?}");
+            format!("{e}")
+        })
+}
+
+#[tauri::command]
+fn set_curseforge_api_key(state: tauri::State<AppState>, key: String) -> Result<(), String> {
+    log::info!("Called set_curseforge_api_key.");
+    let api_key = ApiKey::new(&key).ok_or_else(|| "API key is empty".to_string())?;
+    *state.curseforge_api_key.lock().unwrap() = Some(Arc::new(api_key));
+    Ok(())
+}
+
+#[tauri::command]
+fn clear_curseforge_api_key(state: tauri::State<AppState>) {
+    log::info!("Called clear_curseforge_api_key.");
+    *state.curseforge_api_key.lock().unwrap() = None;
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManualDownloadInfo {
+    pub name: String,
+    pub file_name: String,
+    pub website_url: Option<String>,
+}
+
+#[tauri::command]
+fn get_manual_downloads(state: tauri::State<AppState>) -> Result<Vec<ManualDownloadInfo>, String> {
+    log::info!("Called get_manual_downloads.");
+    Installer::get_manual_downloads(&state.state_path)
+        .map(|manual_downloads| {
+            manual_downloads
+                .into_iter()
+                .map(|m| ManualDownloadInfo {
+                    name: m.name,
+                    file_name: m.file_name,
+                    website_url: m.website_url,
+                })
+                .collect()
+        })
+        .map_err(|e| {
+            log::error!("Failed to get manual downloads: {e:?}");
+            format!("{e}")
+        })
+}
+
+#[tauri::command]
+fn get_default_watch_dir() -> Option<String> {
+    env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" })
+        .map(|home| PathBuf::from(home).join("Downloads"))
+        .filter(|dir| dir.is_dir())
+        .map(|dir| dir.to_string_lossy().to_string())
+}
+
+// Polled by the frontend while the manual download screen is open
+#[tauri::command]
+fn scan_manual_downloads(
+    state: tauri::State<AppState>,
+    dir: String,
+) -> Result<Vec<String>, String> {
+    if *state.is_running.lock().unwrap() {
+        return Err("Installer is running".to_string());
+    }
+    Installer::scan_manual_downloads(
+        &state.install_dir,
+        &state.app_dir,
+        &state.state_path,
+        Path::new(&dir),
+    )
+    .map_err(|e| {
+        log::error!("Failed to scan manual downloads: {e:?}");
+        format!("{e}")
+    })
+}
+
+// Lets the player pick the file in a dialog so that only a path chosen by them is
+// read. Returns None when the dialog is closed.
+#[tauri::command]
+async fn adopt_manual_download(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    log::info!("Called adopt_manual_download.");
+    let state = app.state::<AppState>();
+    if *state.is_running.lock().unwrap() {
+        return Err("Installer is running".to_string());
+    }
+    let Some(file_path) = app.dialog().file().blocking_pick_file() else {
+        return Ok(None);
+    };
+    let path = file_path.into_path().map_err(|e| {
+        log::error!("Failed to read picked path: {e:?}");
+        format!("{e}")
+    })?;
+    Installer::adopt_manual_download(&state.install_dir, &state.app_dir, &state.state_path, &path)
+        .map(Some)
+        .map_err(|e| {
+            log::error!("Failed to adopt manual download: {e:?}");
+            format!("{e}")
+        })
+}
+
+#[tauri::command]
+fn get_cache_usage() -> Result<Option<CacheUsage>, String> {
+    let Some(cache) = DownloadCache::open_default() else {
+        return Ok(None);
+    };
+    cache.usage().map(Some).map_err(|e| {
+        log::error!("Failed to get cache usage: {e:?}");
+        format!("{e}")
+    })
+}
+
+#[tauri::command]
+fn clear_cache(state: tauri::State<AppState>) -> Result<(), String> {
+    log::info!("Called clear_cache.");
+    if *state.is_running.lock().unwrap() {
+        return Err("Installer is running".to_string());
+    }
+    let Some(cache) = DownloadCache::open_default() else {
+        return Ok(());
+    };
+    cache.clear().map_err(|e| {
+        log::error!("Failed to clear cache: {e:?}");
+        format!("{e}")
+    })
+}
+
+// The LAN address suggested for sharing the cache on
+#[tauri::command]
+fn get_local_address() -> Option<String> {
+    peer::local_ip().map(|ip| ip.to_string())
+}
+
+// Shares the verified download cache with other installers on the LAN, listening only
+// on the address the user chose
+#[tauri::command]
+fn start_cache_server(state: tauri::State<AppState>, address: String) -> Result<String, String> {
+    log::info!("Called start_cache_server: {address}");
+    let ip: IpAddr = address
+        .trim()
+        .parse()
+        .map_err(|e| format!("Invalid address: {e}"))?;
+    let mut cache_server = state.cache_server.lock().unwrap();
+    if let Some(server) = cache_server.as_ref() {
+        return Ok(server.url());
+    }
+    let cache = DownloadCache::open_default().ok_or("No download cache on this system")?;
+    let server = CacheServer::start(cache, ip, peer::DEFAULT_PORT).map_err(|e| {
+        log::error!("Failed to start cache server: {e:?}");
+        format!("{e}")
+    })?;
+    let url = server.url();
+    *cache_server = Some(server);
+    Ok(url)
+}
+
+#[tauri::command]
+fn stop_cache_server(state: tauri::State<AppState>) {
+    log::info!("Called stop_cache_server.");
+    *state.cache_server.lock().unwrap() = None;
+}
+
+#[tauri::command]
+fn get_cache_peer(state: tauri::State<AppState>) -> Option<String> {
+    state.cache_peer.lock().unwrap().clone()
+}
+
+// An empty URL stops using a peer
+#[tauri::command]
+fn set_cache_peer(state: tauri::State<AppState>, url: String) -> Result<(), String> {
+    log::info!("Called set_cache_peer: {url}");
+    let url = url.trim();
+    if url.is_empty() {
+        *state.cache_peer.lock().unwrap() = None;
+        return Ok(());
+    }
+    let url = if url.contains("://") {
+        url.to_string()
+    } else {
+        format!("http://{url}")
+    };
+    let parsed = url::Url::parse(&url).map_err(|e| format!("Invalid URL: {e}"))?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(format!("Unsupported URL scheme: {}", parsed.scheme()));
+    }
+    *state.cache_peer.lock().unwrap() = Some(url);
+    Ok(())
+}
+
+#[tauri::command]
+fn quarantine_mod(state: tauri::State<AppState>, file_name: String) -> Result<(), String> {
+    log::info!("Called quarantine_mod: {file_name}");
+    if *state.is_running.lock().unwrap() {
+        return Err("Installer is running".to_string());
+    }
+    Installer::quarantine_mod(
+        &state.install_dir,
+        &state.app_dir,
+        &state.state_path,
+        &file_name,
+    )
+    .map(|_| ())
+    .map_err(|e| {
+        log::error!("Failed to quarantine mod: {e:?}");
+        format!("{e}")
+    })
+}
+
+// Authoring helper for installs without internet access, e.g. at LAN events.
+// Returns the names of files that have to be downloaded manually.
+#[tauri::command]
+async fn create_bundle(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    log::info!("Called create_bundle.");
+    let state = app.state::<AppState>();
+    {
+        let mut is_running = state.is_running.lock().unwrap();
+        if *is_running {
+            return Err("Installer is already running".to_string());
+        }
+        *is_running = true;
+    }
+    let curseforge_api_key = state.curseforge_api_key.lock().unwrap().clone();
+    let cache_peer = state.cache_peer.lock().unwrap().clone();
+    let result = match Installer::new(
+        InstallerMode::Install,
+        app.clone(),
+        state.config_path.clone(),
+        state.install_dir.clone(),
+        state.side,
+        state.app_dir.clone(),
+        state.state_path.clone(),
+    ) {
+        Ok(installer) => {
+            installer
+                .with_curseforge_api_key(curseforge_api_key)
+                .with_cache_peer(cache_peer)
+                .create_bundle()
+                .await
+        }
+        Err(e) => Err(e),
+    }
+    .map_err(|e| {
+        log::error!("Failed to create bundle: {e:?}");
+        format!("{e}")
+    });
+    *state.is_running.lock().unwrap() = false;
+    result
+}
+
 #[tauri::command]
 async fn run_installer(app: tauri::AppHandle, mode: InstallerMode) -> Result<(), String> {
     let state = app.state::<AppState>();
@@ -88,12 +648,15 @@ async fn run_installer(app: tauri::AppHandle, mode: InstallerMode) -> Result<(),
         }
         *is_running = true;
     }
+    let selection = state.selection.lock().unwrap().clone();
+    let curseforge_api_key = state.curseforge_api_key.lock().unwrap().clone();
+    let cache_peer = state.cache_peer.lock().unwrap().clone();
     let result = Installer::new(
         mode,
         app.clone(),
         state.config_path.clone(),
         state.install_dir.clone(),
-        Side::Client,
+        state.side,
         state.app_dir.clone(),
         state.state_path.clone(),
     )
@@ -101,6 +664,9 @@ async fn run_installer(app: tauri::AppHandle, mode: InstallerMode) -> Result<(),
         log::error!("Failed to initialize installer: {e:?}");
         format!("{e}")
     })?
+    .with_selection(selection)
+    .with_curseforge_api_key(curseforge_api_key)
+    .with_cache_peer(cache_peer)
     .run()
     .await
     .map_err(|e| {
@@ -137,12 +703,35 @@ pub fn run() {
                 )])
                 .build(),
         )
+        .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
             initialize_title,
+            fetch_remote_config,
+            get_channels,
+            select_channel,
+            get_options,
             select_mode,
             run_installer,
             open_log_folder,
+            quarantine_mod,
+            validate_config,
+            check_dependencies,
+            resolve_maven_version,
+            create_bundle,
+            get_cache_usage,
+            clear_cache,
+            get_local_address,
+            start_cache_server,
+            stop_cache_server,
+            get_cache_peer,
+            set_cache_peer,
+            set_curseforge_api_key,
+            clear_curseforge_api_key,
+            get_manual_downloads,
+            get_default_watch_dir,
+            scan_manual_downloads,
+            adopt_manual_download,
         ])
         .setup(|app| {
             app.manage(AppState {
@@ -151,10 +740,25 @@ pub fn run() {
                 app_dir,
                 state_path,
                 log_dir,
+                side: Side::Client,
                 is_running: false.into(),
+                selection: None.into(),
+                // Read from the environment at startup and never written to disk.
+                // It can be replaced or discarded at runtime.
+                curseforge_api_key: env::var(curseforge::API_KEY_ENV)
+                    .ok()
+                    .and_then(|key| ApiKey::new(&key))
+                    .map(Arc::new)
+                    .into(),
+                cache_server: None.into(),
+                cache_peer: env::var("MM_INSTALLER_CACHE_PEER")
+                    .ok()
+                    .filter(|url| !url.is_empty())
+                    .into(),
             });
             log::info!("{}", "=".repeat(80));
             log::info!("App version: {}", app.package_info().version);
+            network::init(&app.state::<AppState>().app_dir);
             Ok(())
         })
         .run(tauri::generate_context!())
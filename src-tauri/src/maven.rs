@@ -0,0 +1,292 @@
+use std::fmt::{self, Display};
+
+use anyhow::{bail, Context, Result};
+use reqwest::{Client, StatusCode};
+
+use crate::network::{self, HostAllowlist};
+
+use crate::compat::compare_versions;
+use crate::downloader::RemoteFile;
+
+const DEFAULT_EXTENSION: &str = "jar";
+
+// `group:artifact:version[:classifier][@extension]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MavenArtifact {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+    pub classifier: Option<String>,
+    pub extension: String,
+}
+
+impl MavenArtifact {
+    pub fn parse(coordinates: &str) -> Result<Self> {
+        let (coordinates, extension) = match coordinates.trim().split_once('@') {
+            Some((coordinates, extension)) => (coordinates, extension),
+            None => (coordinates.trim(), DEFAULT_EXTENSION),
+        };
+        let parts: Vec<&str> = coordinates.split(':').collect();
+        let (group, artifact, version, classifier) = match parts[..] {
+            [group, artifact, version] => (group, artifact, version, None),
+            [group, artifact, version, classifier] => (group, artifact, version, Some(classifier)),
+            _ => bail!(
+                "Maven coordinates must be 'group:artifact:version[:classifier][@extension]', got '{coordinates}'"
+            ),
+        };
+        let segments = [group, artifact, version, extension]
+            .into_iter()
+            .chain(classifier);
+        for segment in segments {
+            if segment.is_empty() || segment.contains(['/', '\\']) || segment == ".." {
+                bail!("Maven coordinates contain an invalid segment: '{coordinates}'");
+            }
+        }
+        Ok(Self {
+            group: group.to_string(),
+            artifact: artifact.to_string(),
+            version: version.to_string(),
+            classifier: classifier.map(str::to_string),
+            extension: extension.to_string(),
+        })
+    }
+
+    pub fn file_name(&self) -> String {
+        match &self.classifier {
+            Some(classifier) => format!(
+                "{}-{}-{classifier}.{}",
+                self.artifact, self.version, self.extension
+            ),
+            None => format!("{}-{}.{}", self.artifact, self.version, self.extension),
+        }
+    }
+
+    pub fn url(&self, repository: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            artifact_base_url(repository, &self.group, &self.artifact),
+            self.version,
+            self.file_name()
+        )
+    }
+}
+
+impl Display for MavenArtifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.group, self.artifact, self.version)?;
+        if let Some(classifier) = &self.classifier {
+            write!(f, ":{classifier}")?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Maven;
+
+impl Maven {
+    // Builds the artifact URL and reads the repository's .sha1/.sha256 sidecar files.
+    // They may be the only checksum of the file, so they are held to the allowlist.
+    pub async fn get_remote_file(
+        repository: &str,
+        coordinates: &str,
+        allowlist: &HostAllowlist,
+    ) -> Result<RemoteFile> {
+        let artifact = MavenArtifact::parse(coordinates)?;
+        let url = artifact.url(repository);
+        allowlist.check_str(&url)?;
+        let client = build_client(allowlist)?;
+        let sha1 = get_checksum(&client, &format!("{url}.sha1"), 40).await?;
+        let sha256 = get_checksum(&client, &format!("{url}.sha256"), 64).await?;
+        Ok(RemoteFile {
+            file_name: Some(artifact.file_name()),
+            sha1,
+            sha256,
+            ..RemoteFile::from_url(&url)
+        })
+    }
+
+    // Finds the newest release of `group:artifact` for a Minecraft version in
+    // maven-metadata.xml. Supports versions prefixed with the Minecraft version
+    // (Forge, `1.20.1-47.2.0`) and NeoForge's `<minor>.<patch>.<build>`.
+    pub async fn resolve_latest_version(
+        repository: &str,
+        group_artifact: &str,
+        minecraft_version: &str,
+    ) -> Result<Option<String>> {
+        let Some((group, artifact)) = group_artifact.trim().split_once(':') else {
+            bail!("Expected 'group:artifact', got '{group_artifact}'");
+        };
+        let url = format!(
+            "{}/maven-metadata.xml",
+            artifact_base_url(repository, group, artifact)
+        );
+        let response = network::client_builder()?
+            .build()
+            .context("Failed to build HTTP client")?
+            .get(&url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to request {url}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("Request to {url} failed with status {status}.");
+        }
+        let metadata = response
+            .text()
+            .await
+            .with_context(|| format!("Failed to read {url}"))?;
+        Ok(find_latest_version(&metadata, minecraft_version))
+    }
+}
+
+fn find_latest_version(metadata: &str, minecraft_version: &str) -> Option<String> {
+    let neoforge_prefix = neoforge_version_prefix(minecraft_version);
+    let forge_prefix = format!("{minecraft_version}-");
+    metadata
+        .split("<version>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</version>").map(|(v, _)| v.trim()))
+        .filter_map(|version| {
+            let build = version.strip_prefix(forge_prefix.as_str()).or_else(|| {
+                neoforge_prefix
+                    .as_deref()
+                    .filter(|prefix| version.starts_with(prefix))
+                    .map(|_| version)
+            })?;
+            // Skip pre-releases such as `21.1.0-beta`
+            (!build.contains('-')).then_some((version, build))
+        })
+        // Builds are compared alone, as the Minecraft prefix would make them pre-releases
+        .max_by(|(_, a), (_, b)| compare_versions(a, b))
+        .map(|(version, _)| version.to_string())
+}
+
+fn build_client(allowlist: &HostAllowlist) -> Result<Client> {
+    network::client_builder()?
+        .redirect(allowlist.redirect_policy())
+        .build()
+        .context("Failed to build HTTP client")
+}
+
+fn artifact_base_url(repository: &str, group: &str, artifact: &str) -> String {
+    format!(
+        "{}/{}/{artifact}",
+        repository.trim_end_matches('/'),
+        group.replace('.', "/")
+    )
+}
+
+// Minecraft `1.21.1` maps to NeoForge `21.1.`, `1.21` to `21.0.`
+fn neoforge_version_prefix(minecraft_version: &str) -> Option<String> {
+    let mut parts = minecraft_version.strip_prefix("1.")?.split('.');
+    let minor: u32 = parts.next()?.parse().ok()?;
+    let patch: u32 = parts.next().map_or(Some(0), |p| p.parse().ok())?;
+    Some(format!("{minor}.{patch}."))
+}
+
+// Missing sidecars are not an error; the caller decides whether the file can be verified
+async fn get_checksum(client: &Client, url: &str, length: usize) -> Result<Option<String>> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to request {url}"))?;
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        log::info!("No checksum file at {url}");
+        return Ok(None);
+    }
+    if !status.is_success() {
+        bail!("Request to {url} failed with status {status}.");
+    }
+    let body = response
+        .text()
+        .await
+        .with_context(|| format!("Failed to read {url}"))?;
+    // Some repositories append the file name after the checksum
+    let checksum = body.split_whitespace().next().unwrap_or_default();
+    if checksum.len() != length || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("Invalid checksum file at {url}");
+    }
+    Ok(Some(checksum.to_ascii_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    const METADATA: &str = "<metadata><versioning><versions>
+        <version>1.20.1-47.1.0</version>
+        <version>1.20.1-47.2.0</version>
+        <version>1.20.1-47.10.0</version>
+        <version>1.20.2-48.0.0</version>
+        <version>21.1.9</version>
+        <version>21.1.10</version>
+        <version>21.1.11-beta</version>
+        <version>21.0.5</version>
+        <version>21.10.1</version>
+    </versions></versioning></metadata>";
+
+    #[test]
+    fn finds_latest_forge_version() {
+        assert_eq!(
+            find_latest_version(METADATA, "1.20.1").as_deref(),
+            Some("1.20.1-47.10.0")
+        );
+    }
+
+    #[test]
+    fn finds_latest_neoforge_release() {
+        assert_eq!(
+            find_latest_version(METADATA, "1.21.1").as_deref(),
+            Some("21.1.10")
+        );
+        assert_eq!(
+            find_latest_version(METADATA, "1.21").as_deref(),
+            Some("21.0.5")
+        );
+    }
+
+    #[test]
+    fn finds_nothing_for_other_versions() {
+        assert_eq!(find_latest_version(METADATA, "1.19.2"), None);
+        assert_eq!(find_latest_version("", "1.20.1"), None);
+    }
+
+    #[test]
+    fn maps_minecraft_to_neoforge_prefix() {
+        assert_eq!(neoforge_version_prefix("1.21.1").as_deref(), Some("21.1."));
+        assert_eq!(neoforge_version_prefix("1.21").as_deref(), Some("21.0."));
+        assert_eq!(neoforge_version_prefix("24w14a"), None);
+    }
+
+    // Serves one request with a redirect to `location`
+    fn redirect_once(location: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            let response =
+                format!("HTTP/1.1 302 Found\r\nLocation: {location}\r\nContent-Length: 0\r\n\r\n");
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://{address}/net/example/mod/1.0/mod-1.0.jar.sha1")
+    }
+
+    #[tokio::test]
+    async fn rejects_checksum_redirects_to_other_hosts() {
+        let url = redirect_once("https://evil.example.com/mod-1.0.jar.sha1");
+        let client = build_client(&HostAllowlist::new(&[])).unwrap();
+        let error = get_checksum(&client, &url, 40).await.unwrap_err();
+        assert!(
+            format!("{error:?}").contains("Refused redirect"),
+            "{error:?}"
+        );
+    }
+}
@@ -1,21 +1,180 @@
-use std::sync::LazyLock;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
 
-use anyhow::{anyhow, Result};
-use ferinth::Ferinth;
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::{Client, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 
-static FERINTH: LazyLock<Ferinth<()>> = LazyLock::new(|| {
-    Ferinth::<()>::new(
-        env!("CARGO_PKG_NAME"),
-        Some(env!("CARGO_PKG_VERSION")),
-        None,
-    )
-});
+use crate::compat::GameTarget;
+use crate::config::{ModEntry, ModPackConfig, Selection, Side, SourceType};
+use crate::downloader::RemoteFile;
+use crate::network;
+
+const API_URL: &str = "https://api.modrinth.com/v2";
+// Keeps the URL well below common request line limits
+const VERSIONS_PER_REQUEST: usize = 100;
+
+// Shared so that connections to the API are reused
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Version {
+    pub id: String,
+    pub project_id: String,
+    #[serde(default)]
+    pub files: Vec<VersionFile>,
+    #[serde(default)]
+    pub dependencies: Vec<Dependency>,
+    #[serde(default)]
+    pub game_versions: Vec<String>,
+    #[serde(default)]
+    pub loaders: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct VersionFile {
+    pub hashes: FileHashes,
+    pub url: String,
+    pub filename: String,
+    #[serde(default)]
+    pub primary: bool,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct FileHashes {
+    pub sha1: String,
+    pub sha512: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Dependency {
+    pub version_id: Option<String>,
+    pub project_id: Option<String>,
+    pub dependency_type: DependencyType,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyType {
+    Required,
+    Optional,
+    Incompatible,
+    Embedded,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+struct Project {
+    title: String,
+}
+
+// Primary files of resolved versions. Version IDs are immutable, so entries never expire.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModrinthCache {
+    versions: HashMap<String, CachedVersion>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedVersion {
+    project_id: String,
+    url: String,
+    file_name: String,
+    sha1: String,
+    sha512: String,
+    size: u64,
+    // Missing in caches written by older installers
+    #[serde(default)]
+    game_versions: Vec<String>,
+    #[serde(default)]
+    loaders: Vec<String>,
+}
+
+impl CachedVersion {
+    fn to_remote_file(&self) -> RemoteFile {
+        RemoteFile {
+            url: self.url.clone(),
+            file_name: Some(self.file_name.clone()),
+            sha1: Some(self.sha1.clone()),
+            sha512: Some(self.sha512.clone()),
+            size: Some(self.size),
+            ..Default::default()
+        }
+    }
+}
+
+impl ModrinthCache {
+    fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+        fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|raw| Ok(serde_json::from_str(&raw)?))
+            .unwrap_or_else(|e| {
+                log::warn!("Ignoring broken Modrinth cache {}: {e:?}", path.display());
+                Self::default()
+            })
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(self).context("Failed to serialize Modrinth cache")?;
+        fs::write(path, json)
+            .with_context(|| format!("Failed to write Modrinth cache to {}", path.display()))?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DependencyIssueKind {
+    Missing,
+    Incompatible,
+}
+
+// A dependency problem that the authoring UI can offer to fix
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyIssue {
+    pub kind: DependencyIssueKind,
+    pub mod_name: String,
+    pub project_id: String,
+    pub project_title: String,
+    // Version pinned by the dependent mod, or the newest one for the profile
+    pub version_id: Option<String>,
+}
+
+impl Display for DependencyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            DependencyIssueKind::Missing => write!(
+                f,
+                "{} requires {} ({}), which is not in mods",
+                self.mod_name, self.project_title, self.project_id
+            ),
+            DependencyIssueKind::Incompatible => write!(
+                f,
+                "{} is incompatible with {} ({}), which is in mods",
+                self.mod_name, self.project_title, self.project_id
+            ),
+        }
+    }
+}
 
 pub struct Modrinth;
 
 impl Modrinth {
-    pub async fn get_download_url(project_id: &str, version_id: &str) -> Result<String> {
-        let version = FERINTH.version_get(version_id).await?;
+    pub async fn get_version(project_id: &str, version_id: &str) -> Result<Version> {
+        let version: Version =
+            get(&format!("/version/{}", urlencoding::encode(version_id))).await?;
         if version.project_id != project_id {
             return Err(anyhow!(
                 "Project ID mismatch: expected {}, got {}.",
@@ -23,19 +182,553 @@ impl Modrinth {
                 version.project_id
             ));
         }
-        let file = version
+        Ok(version)
+    }
+
+    // Walks required dependencies transitively, and incompatible ones, of every mod
+    // installed for `selection` on `side`. Entries from other sources are matched to
+    // Modrinth projects by their SHA-1. `versions` holds versions already fetched,
+    // keyed by version ID, and collects the ones fetched here.
+    pub async fn check_dependencies(
+        config: &ModPackConfig,
+        selection: &Selection,
+        side: &Side,
+        versions: &mut HashMap<String, Version>,
+    ) -> Result<Vec<DependencyIssue>> {
+        walk_dependencies(&ModrinthApi, config, selection, side, versions).await
+    }
+
+    // The newest version of a project for the profile's Minecraft version and loader
+    async fn get_latest_version(project_id: &str, target: &GameTarget) -> Result<Option<Version>> {
+        let mut path = format!("/project/{}/version?", urlencoding::encode(project_id));
+        if let Some(loader) = target.loader {
+            let loaders = serde_json::to_string(&[loader.modrinth_name()])?;
+            path.push_str(&format!("loaders={}&", urlencoding::encode(&loaders)));
+        }
+        if let Some(minecraft) = &target.minecraft {
+            let game_versions = serde_json::to_string(&[minecraft])?;
+            path.push_str(&format!(
+                "game_versions={}",
+                urlencoding::encode(&game_versions)
+            ));
+        }
+        let versions: Vec<Version> = get(path.trim_end_matches(['?', '&'])).await?;
+        Ok(versions.into_iter().next())
+    }
+
+    // Versions of files whose SHA-1 Modrinth knows, keyed by lowercase SHA-1
+    async fn get_versions_by_hash(hashes: &[&str]) -> Result<HashMap<String, Version>> {
+        if hashes.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let hashes: Vec<String> = hashes.iter().map(|h| h.to_ascii_lowercase()).collect();
+        post(
+            "/version_files",
+            &serde_json::json!({ "hashes": hashes, "algorithm": "sha1" }),
+        )
+        .await
+    }
+
+    pub fn get_primary_file(version: &Version) -> Option<&VersionFile> {
+        version
             .files
             .iter()
             .find(|f| f.primary)
             .or_else(|| version.files.first())
-            .ok_or_else(|| {
-                anyhow!(
-                    "No files found for version {} of project {}.",
-                    version_id,
-                    project_id
-                )
+    }
+
+    // Resolves primary files of many versions with the bulk endpoint, using the cache first.
+    // `versions` are pairs of project ID and version ID. Returns files keyed by version ID.
+    pub async fn resolve_remote_files(
+        versions: &[(&str, &str)],
+        cache_path: &Path,
+    ) -> Result<HashMap<String, RemoteFile>> {
+        let mut cache = ModrinthCache::load(cache_path);
+        let missing_ids: Vec<&str> = versions
+            .iter()
+            .filter(|(_, version_id)| {
+                cache
+                    .versions
+                    .get(*version_id)
+                    .map_or(true, |cached| cached.loaders.is_empty())
+            })
+            .map(|(_, version_id)| *version_id)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        log::info!(
+            "Resolving {} Modrinth versions ({} cached)...",
+            versions.len(),
+            versions.len() - missing_ids.len()
+        );
+        for chunk in missing_ids.chunks(VERSIONS_PER_REQUEST) {
+            let ids = serde_json::to_string(chunk)?;
+            let versions: Vec<Version> =
+                get(&format!("/versions?ids={}", urlencoding::encode(&ids))).await?;
+            for version in versions {
+                let Some(file) = Self::get_primary_file(&version) else {
+                    continue;
+                };
+                cache.versions.insert(
+                    version.id.clone(),
+                    CachedVersion {
+                        project_id: version.project_id.clone(),
+                        url: file.url.to_string(),
+                        file_name: file.filename.clone(),
+                        sha1: file.hashes.sha1.clone(),
+                        sha512: file.hashes.sha512.clone(),
+                        size: file.size,
+                        game_versions: version.game_versions.clone(),
+                        loaders: version.loaders.clone(),
+                    },
+                );
+            }
+        }
+        if !missing_ids.is_empty() {
+            cache.save(cache_path)?;
+        }
+        let mut remote_files = HashMap::new();
+        for (project_id, version_id) in versions {
+            let cached = cache.versions.get(*version_id).ok_or_else(|| {
+                anyhow!("No files found for version {version_id} of project {project_id}.")
             })?;
+            if cached.project_id != *project_id {
+                return Err(anyhow!(
+                    "Project ID mismatch: expected {}, got {}.",
+                    project_id,
+                    cached.project_id
+                ));
+            }
+            remote_files.insert(version_id.to_string(), cached.to_remote_file());
+        }
+        Ok(remote_files)
+    }
+
+    // Game versions and loaders of resolved versions, keyed by version ID. Works offline.
+    pub fn get_cached_targets(cache_path: &Path) -> HashMap<String, (Vec<String>, Vec<String>)> {
+        ModrinthCache::load(cache_path)
+            .versions
+            .into_iter()
+            .map(|(version_id, cached)| (version_id, (cached.game_versions, cached.loaders)))
+            .collect()
+    }
+
+    // Returns the primary file with the hashes, size and file name published by Modrinth
+    pub async fn get_remote_file(project_id: &str, version_id: &str) -> Result<RemoteFile> {
+        let version = Self::get_version(project_id, version_id).await?;
+        let file = Self::get_primary_file(&version).ok_or_else(|| {
+            anyhow!(
+                "No files found for version {} of project {}.",
+                version_id,
+                project_id
+            )
+        })?;
+
+        Ok(RemoteFile {
+            url: file.url.to_string(),
+            file_name: Some(file.filename.clone()),
+            sha1: Some(file.hashes.sha1.clone()),
+            sha512: Some(file.hashes.sha512.clone()),
+            size: Some(file.size),
+            ..Default::default()
+        })
+    }
+}
+
+// The API calls made by the dependency walk, so that tests can answer them
+trait DependencyApi {
+    async fn get_version(&self, version_id: &str) -> Result<Version>;
+    async fn get_project_title(&self, project_id: &str) -> Result<String>;
+    async fn get_latest_version(
+        &self,
+        project_id: &str,
+        target: &GameTarget,
+    ) -> Result<Option<Version>>;
+    async fn get_versions_by_hash(&self, hashes: &[&str]) -> Result<HashMap<String, Version>>;
+}
+
+struct ModrinthApi;
+
+impl DependencyApi for ModrinthApi {
+    async fn get_version(&self, version_id: &str) -> Result<Version> {
+        get(&format!("/version/{}", urlencoding::encode(version_id))).await
+    }
+
+    async fn get_project_title(&self, project_id: &str) -> Result<String> {
+        let project: Project =
+            get(&format!("/project/{}", urlencoding::encode(project_id))).await?;
+        Ok(project.title)
+    }
+
+    async fn get_latest_version(
+        &self,
+        project_id: &str,
+        target: &GameTarget,
+    ) -> Result<Option<Version>> {
+        Modrinth::get_latest_version(project_id, target).await
+    }
+
+    async fn get_versions_by_hash(&self, hashes: &[&str]) -> Result<HashMap<String, Version>> {
+        Modrinth::get_versions_by_hash(hashes).await
+    }
+}
+
+async fn walk_dependencies(
+    api: &impl DependencyApi,
+    config: &ModPackConfig,
+    selection: &Selection,
+    side: &Side,
+    versions: &mut HashMap<String, Version>,
+) -> Result<Vec<DependencyIssue>> {
+    let profile = config.get_variant_profile(selection.variant.as_deref());
+    let target = GameTarget::parse(&profile.version, config.get_mod_loader());
+    let mod_entries: Vec<&ModEntry> = config
+        .get_mods()
+        .iter()
+        .filter(|entry| entry.should_install(side, selection))
+        .collect();
+    let hashes: Vec<&str> = mod_entries
+        .iter()
+        .filter(|entry| !matches!(entry.source, SourceType::Modrinth { .. }))
+        .filter(|entry| !entry.hash.is_empty())
+        .map(|entry| entry.hash.as_str())
+        .collect();
+    let mut versions_by_hash = api.get_versions_by_hash(&hashes).await?;
+
+    // Versions to walk, with the name of the mod they belong to
+    let mut pending: Vec<(String, String)> = Vec::new();
+    let mut project_ids: HashSet<String> = HashSet::new();
+    for mod_entry in mod_entries {
+        match &mod_entry.source {
+            SourceType::Modrinth {
+                project_id,
+                version_id,
+            } => {
+                let version = fetch_version(api, version_id, versions)
+                    .await
+                    .with_context(|| {
+                        format!("Failed to get Modrinth version of {}", mod_entry.name)
+                    })?;
+                if version.project_id != *project_id {
+                    bail!(
+                        "Project ID mismatch for {}: expected {}, got {}.",
+                        mod_entry.name,
+                        project_id,
+                        version.project_id
+                    );
+                }
+                project_ids.insert(project_id.clone());
+                pending.push((mod_entry.name.clone(), version_id.clone()));
+            }
+            _ => {
+                let Some(version) = versions_by_hash.remove(&mod_entry.hash.to_ascii_lowercase())
+                else {
+                    continue;
+                };
+                log::info!("{} is {} on Modrinth", mod_entry.name, version.project_id);
+                project_ids.insert(version.project_id.clone());
+                pending.push((mod_entry.name.clone(), version.id.clone()));
+                versions.entry(version.id.clone()).or_insert(version);
+            }
+        }
+    }
+
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut project_titles: HashMap<String, String> = HashMap::new();
+    let mut issues = Vec::new();
+    while let Some((mod_name, version_id)) = pending.pop() {
+        if !visited.insert(version_id.clone()) {
+            continue;
+        }
+        let dependencies = versions[&version_id].dependencies.clone();
+        for dependency in dependencies {
+            let kind = match dependency.dependency_type {
+                DependencyType::Required => DependencyIssueKind::Missing,
+                DependencyType::Incompatible => DependencyIssueKind::Incompatible,
+                _ => continue,
+            };
+            let dependency_project_id = match (dependency.project_id, &dependency.version_id) {
+                (Some(project_id), _) => project_id,
+                (None, Some(version_id)) => fetch_version(api, version_id, versions)
+                    .await?
+                    .project_id
+                    .clone(),
+                (None, None) => continue,
+            };
+            let is_present = project_ids.contains(&dependency_project_id);
+            if is_present != (kind == DependencyIssueKind::Incompatible) {
+                continue;
+            }
+            if !project_titles.contains_key(&dependency_project_id) {
+                let title = api.get_project_title(&dependency_project_id).await?;
+                project_titles.insert(dependency_project_id.clone(), title);
+            }
+            let project_title = project_titles[&dependency_project_id].clone();
+            // A missing dependency brings its own required dependencies
+            let dependency_version_id = match (kind, dependency.version_id) {
+                (DependencyIssueKind::Missing, Some(version_id)) => {
+                    fetch_version(api, &version_id, versions).await?;
+                    Some(version_id)
+                }
+                (DependencyIssueKind::Missing, None) => {
+                    let version = api
+                        .get_latest_version(&dependency_project_id, &target)
+                        .await?;
+                    version.map(|version| {
+                        let version_id = version.id.clone();
+                        versions.entry(version_id.clone()).or_insert(version);
+                        version_id
+                    })
+                }
+                (_, version_id) => version_id,
+            };
+            if kind == DependencyIssueKind::Missing {
+                if let Some(version_id) = &dependency_version_id {
+                    pending.push((project_title.clone(), version_id.clone()));
+                }
+            }
+            issues.push(DependencyIssue {
+                kind,
+                mod_name: mod_name.clone(),
+                project_title,
+                project_id: dependency_project_id,
+                version_id: dependency_version_id,
+            });
+        }
+    }
+    Ok(issues)
+}
+
+async fn fetch_version<'a>(
+    api: &impl DependencyApi,
+    version_id: &str,
+    versions: &'a mut HashMap<String, Version>,
+) -> Result<&'a Version> {
+    if !versions.contains_key(version_id) {
+        let version = api.get_version(version_id).await?;
+        versions.insert(version_id.to_string(), version);
+    }
+    Ok(&versions[version_id])
+}
+
+fn client() -> Result<&'static Client> {
+    if let Some(client) = CLIENT.get() {
+        return Ok(client);
+    }
+    let client = network::client_builder()?
+        .build()
+        .context("Failed to build HTTP client")?;
+    Ok(CLIENT.get_or_init(|| client))
+}
+
+async fn get<T: DeserializeOwned>(path: &str) -> Result<T> {
+    let url = format!("{API_URL}{path}");
+    let response = client()?
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to request {url}"))?;
+    parse_response(&url, response).await
+}
+
+async fn post<T: DeserializeOwned>(path: &str, body: &serde_json::Value) -> Result<T> {
+    let url = format!("{API_URL}{path}");
+    let response = client()?
+        .post(&url)
+        .json(body)
+        .send()
+        .await
+        .with_context(|| format!("Failed to request {url}"))?;
+    parse_response(&url, response).await
+}
+
+async fn parse_response<T: DeserializeOwned>(url: &str, response: Response) -> Result<T> {
+    let status = response.status();
+    if !status.is_success() {
+        bail!("Request to {url} failed with status {status}.");
+    }
+    response
+        .json()
+        .await
+        .with_context(|| format!("Failed to parse response from {url}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+
+    const CONFIG: &str = r#"
+schemaVersion: 4
+packVersion: "1.0.0"
+profile:
+  name: Test
+  icon: ""
+  version: neoforge-21.1.215
+modLoader:
+  name: NeoForge
+  url: https://github.com/example/loader/releases/download/v1/installer.jar
+  hash: 0000000000000000000000000000000000000000
+mods:
+  - name: Mod A
+    type: modrinth
+    projectId: a
+    versionId: a1
+    hash: 1111111111111111111111111111111111111111
+    side: both
+  - name: Mod D
+    type: github
+    repo: example/d
+    tag: v1
+    asset: d.jar
+    hash: DDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDD
+    side: both
+"#;
+
+    // Answers from canned version JSON, keyed by version ID
+    struct FakeApi {
+        versions: HashMap<String, Version>,
+    }
+
+    impl FakeApi {
+        fn new(versions: &str) -> Self {
+            let versions: Vec<Version> = serde_json::from_str(versions).unwrap();
+            Self {
+                versions: versions.into_iter().map(|v| (v.id.clone(), v)).collect(),
+            }
+        }
+    }
+
+    impl DependencyApi for FakeApi {
+        async fn get_version(&self, version_id: &str) -> Result<Version> {
+            self.versions
+                .get(version_id)
+                .cloned()
+                .with_context(|| format!("No version {version_id}"))
+        }
+
+        async fn get_project_title(&self, project_id: &str) -> Result<String> {
+            Ok(format!("Project {}", project_id.to_ascii_uppercase()))
+        }
+
+        // The latest version of a project is `<project_id>-latest`
+        async fn get_latest_version(
+            &self,
+            project_id: &str,
+            _target: &GameTarget,
+        ) -> Result<Option<Version>> {
+            Ok(self.versions.get(&format!("{project_id}-latest")).cloned())
+        }
+
+        // Only Mod D's file is known, as version d1
+        async fn get_versions_by_hash(&self, hashes: &[&str]) -> Result<HashMap<String, Version>> {
+            let hash = "d".repeat(40);
+            Ok(hashes
+                .iter()
+                .filter(|h| h.eq_ignore_ascii_case(&hash))
+                .filter_map(|_| self.versions.get("d1").cloned())
+                .map(|version| (hash.clone(), version))
+                .collect())
+        }
+    }
+
+    fn walk(versions: &str) -> Result<Vec<DependencyIssue>> {
+        let config = ModPackConfig::load_from_str(CONFIG).unwrap();
+        walk_dependencies(
+            &FakeApi::new(versions),
+            &config,
+            &Selection::default(),
+            &Side::Client,
+            &mut HashMap::new(),
+        )
+        .now_or_never()
+        .expect("canned answers are ready immediately")
+    }
+
+    fn summary(issues: &[DependencyIssue]) -> Vec<(DependencyIssueKind, &str, &str, Option<&str>)> {
+        let mut summary: Vec<_> = issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.kind,
+                    issue.mod_name.as_str(),
+                    issue.project_id.as_str(),
+                    issue.version_id.as_deref(),
+                )
+            })
+            .collect();
+        summary.sort_by(|a, b| a.2.cmp(b.2));
+        summary
+    }
+
+    #[test]
+    fn walks_missing_dependencies_transitively() {
+        let issues = walk(
+            r#"[
+                {"id": "a1", "project_id": "a", "dependencies": [
+                    {"project_id": "b", "version_id": "b1", "dependency_type": "required"},
+                    {"project_id": "x", "dependency_type": "optional"}
+                ]},
+                {"id": "b1", "project_id": "b", "dependencies": [
+                    {"project_id": "c", "dependency_type": "required"}
+                ]},
+                {"id": "c-latest", "project_id": "c", "dependencies": []},
+                {"id": "d1", "project_id": "d", "dependencies": []}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            summary(&issues),
+            vec![
+                (DependencyIssueKind::Missing, "Mod A", "b", Some("b1")),
+                (
+                    DependencyIssueKind::Missing,
+                    "Project B",
+                    "c",
+                    Some("c-latest")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_dependencies_that_are_installed() {
+        // Mod D is matched to project d by its SHA-1
+        let issues = walk(
+            r#"[
+                {"id": "a1", "project_id": "a", "dependencies": [
+                    {"project_id": "d", "dependency_type": "required"},
+                    {"version_id": "d1", "dependency_type": "required"}
+                ]},
+                {"id": "d1", "project_id": "d", "dependencies": []}
+            ]"#,
+        )
+        .unwrap();
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn reports_incompatible_mods_that_are_installed() {
+        let issues = walk(
+            r#"[
+                {"id": "a1", "project_id": "a", "dependencies": [
+                    {"project_id": "e", "dependency_type": "incompatible"}
+                ]},
+                {"id": "d1", "project_id": "d", "dependencies": [
+                    {"version_id": "a1", "dependency_type": "incompatible"}
+                ]}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            summary(&issues),
+            vec![(DependencyIssueKind::Incompatible, "Mod D", "a", Some("a1"))]
+        );
+    }
 
-        Ok(file.url.to_string())
+    #[test]
+    fn rejects_versions_of_other_projects() {
+        let result = walk(r#"[{"id": "a1", "project_id": "z", "dependencies": []}]"#);
+        assert!(result.is_err());
     }
 }
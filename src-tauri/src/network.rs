@@ -0,0 +1,279 @@
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use reqwest::redirect::Policy;
+use reqwest::{ClientBuilder, Proxy};
+use serde::Deserialize;
+use url::Url;
+
+// Modrinth asks API clients to identify themselves with a way to contact the author
+pub const USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (+",
+    env!("CARGO_PKG_REPOSITORY"),
+    ")"
+);
+// Per-machine settings next to the installer state
+pub const SETTINGS_FILE: &str = "network.json";
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_READ_TIMEOUT_SECS: u64 = 60;
+// Disables the system proxy instead of naming one
+const NO_PROXY: &str = "none";
+// Hosts downloads may come from, including their subdomains. Packs can add more.
+const DEFAULT_ALLOWED_HOSTS: &[&str] = &[
+    "modrinth.com",
+    "curseforge.com",
+    "forgecdn.net",
+    "github.com",
+    "githubusercontent.com",
+    "neoforged.net",
+    "minecraftforge.net",
+    "fabricmc.net",
+    "quiltmc.org",
+];
+const MAX_REDIRECTS: usize = 10;
+
+static SETTINGS: OnceLock<NetworkSettings> = OnceLock::new();
+static LIMITER: OnceLock<Option<Arc<BandwidthLimiter>>> = OnceLock::new();
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSettings {
+    // `http://`, `https://`, `socks5://` or `socks5h://` URL, optionally with
+    // `user:password@`. The system proxy is used when unset.
+    pub proxy: Option<String>,
+    pub connect_timeout_secs: Option<u64>,
+    // Maximum time without receiving data, not the time for a whole download
+    pub read_timeout_secs: Option<u64>,
+    // Shared by all downloads
+    pub max_download_kib_per_second: Option<u64>,
+}
+
+impl NetworkSettings {
+    fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+// Loads `network.json` once at startup. Invalid settings are logged and ignored
+// so that a typo does not lock the user out of every download.
+pub fn init(app_dir: &Path) {
+    let path = app_dir.join(SETTINGS_FILE);
+    let settings = NetworkSettings::load(&path).unwrap_or_else(|e| {
+        log::error!("Failed to load network settings: {e:?}");
+        NetworkSettings::default()
+    });
+    if let Some(proxy) = &settings.proxy {
+        log::info!("Using proxy setting: {}", redact_proxy(proxy));
+    }
+    if let Some(limit) = settings.max_download_kib_per_second {
+        log::info!("Limiting downloads to {limit} KiB/s");
+    }
+    let _ = SETTINGS.set(settings);
+}
+
+fn settings() -> &'static NetworkSettings {
+    SETTINGS.get_or_init(NetworkSettings::default)
+}
+
+// Every HTTP client starts from this so that timeouts, proxy and User-Agent apply everywhere
+pub fn client_builder() -> Result<ClientBuilder> {
+    let settings = settings();
+    let mut builder = ClientBuilder::new()
+        .user_agent(USER_AGENT)
+        .connect_timeout(Duration::from_secs(
+            settings
+                .connect_timeout_secs
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS),
+        ))
+        .read_timeout(Duration::from_secs(
+            settings
+                .read_timeout_secs
+                .unwrap_or(DEFAULT_READ_TIMEOUT_SECS),
+        ));
+    match settings.proxy.as_deref().map(str::trim) {
+        None | Some("") => {}
+        Some(NO_PROXY) => builder = builder.no_proxy(),
+        Some(proxy) => {
+            let proxy = Proxy::all(proxy)
+                .with_context(|| format!("Invalid proxy URL: {}", redact_proxy(proxy)))?;
+            builder = builder.proxy(proxy);
+        }
+    }
+    Ok(builder)
+}
+
+pub fn bandwidth_limiter() -> Option<Arc<BandwidthLimiter>> {
+    LIMITER
+        .get_or_init(|| {
+            settings()
+                .max_download_kib_per_second
+                .filter(|limit| *limit > 0)
+                .map(|limit| Arc::new(BandwidthLimiter::new(limit * 1024)))
+        })
+        .clone()
+}
+
+// A token bucket holding up to one second of transfer. Downloads reserve
+// bytes as they receive them and sleep off any debt, so concurrent
+// downloads share the limit.
+pub struct BandwidthLimiter {
+    bytes_per_second: u64,
+    state: Mutex<(Instant, f64)>,
+}
+
+impl BandwidthLimiter {
+    fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second,
+            state: Mutex::new((Instant::now(), bytes_per_second as f64)),
+        }
+    }
+
+    pub async fn consume(&self, bytes: usize) {
+        let delay = {
+            let mut state = self.state.lock().unwrap();
+            let (last, tokens) = &mut *state;
+            let now = Instant::now();
+            let rate = self.bytes_per_second as f64;
+            *tokens = (*tokens + now.duration_since(*last).as_secs_f64() * rate).min(rate);
+            *last = now;
+            *tokens -= bytes as f64;
+            if *tokens < 0.0 {
+                Duration::from_secs_f64(-*tokens / rate)
+            } else {
+                Duration::ZERO
+            }
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HostAllowlist {
+    hosts: Vec<String>,
+}
+
+impl HostAllowlist {
+    pub fn new(extra_hosts: &[String]) -> Self {
+        let hosts = DEFAULT_ALLOWED_HOSTS
+            .iter()
+            .map(|host| host.to_string())
+            .chain(
+                extra_hosts
+                    .iter()
+                    .map(|host| host.trim().to_ascii_lowercase()),
+            )
+            .collect();
+        Self { hosts }
+    }
+
+    pub fn is_allowed(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        self.hosts.iter().any(|allowed| {
+            host == *allowed
+                || host
+                    .strip_suffix(allowed.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        })
+    }
+
+    // Downloads must use HTTPS and come from an allowed host
+    pub fn check(&self, url: &Url) -> Result<()> {
+        if url.scheme() != "https" {
+            bail!("Download URL must use HTTPS: {url}");
+        }
+        match url.host_str() {
+            Some(host) if self.is_allowed(host) => Ok(()),
+            Some(host) => bail!("Host '{host}' is not in the allowed hosts: {url}"),
+            None => bail!("Download URL has no host: {url}"),
+        }
+    }
+
+    pub fn check_str(&self, url: &str) -> Result<()> {
+        self.check(&Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?)
+    }
+
+    // Applies the same check to every redirect hop
+    pub fn redirect_policy(&self) -> Policy {
+        let allowlist = self.clone();
+        Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                let message = format!("Too many redirects to {}", attempt.url());
+                return attempt.error(message);
+            }
+            match allowlist.check(attempt.url()) {
+                Ok(()) => attempt.follow(),
+                Err(e) => attempt.error(format!("Refused redirect: {e}")),
+            }
+        })
+    }
+}
+
+// Keeps proxy passwords out of the log
+fn redact_proxy(proxy: &str) -> String {
+    match url::Url::parse(proxy) {
+        Ok(mut url) if url.password().is_some() => {
+            let _ = url.set_password(Some("***"));
+            url.to_string()
+        }
+        _ => proxy.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_hosts_and_subdomains() {
+        let allowlist = HostAllowlist::new(&[]);
+        assert!(allowlist.is_allowed("modrinth.com"));
+        assert!(allowlist.is_allowed("cdn.modrinth.com"));
+        assert!(allowlist.is_allowed("edge.forgecdn.net"));
+        assert!(allowlist.is_allowed("CDN.Modrinth.com."));
+    }
+
+    #[test]
+    fn rejects_lookalike_hosts() {
+        let allowlist = HostAllowlist::new(&[]);
+        assert!(!allowlist.is_allowed("evilmodrinth.com"));
+        assert!(!allowlist.is_allowed("modrinth.com.example.net"));
+        assert!(!allowlist.is_allowed("com"));
+        assert!(!allowlist.is_allowed(""));
+    }
+
+    #[test]
+    fn allows_extra_hosts() {
+        let allowlist = HostAllowlist::new(&[" Files.Example.org ".to_string()]);
+        assert!(allowlist.is_allowed("files.example.org"));
+        assert!(allowlist.is_allowed("eu.files.example.org"));
+        assert!(!allowlist.is_allowed("example.org"));
+    }
+
+    #[test]
+    fn requires_https() {
+        let allowlist = HostAllowlist::new(&[]);
+        assert!(allowlist
+            .check_str("https://cdn.modrinth.com/data/a.jar")
+            .is_ok());
+        assert!(allowlist
+            .check_str("http://cdn.modrinth.com/data/a.jar")
+            .is_err());
+        assert!(allowlist.check_str("https://example.com/a.jar").is_err());
+        assert!(allowlist.check_str("file:///etc/passwd").is_err());
+    }
+}
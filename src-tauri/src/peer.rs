@@ -0,0 +1,141 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::cache::DownloadCache;
+
+// Fixed so that other machines can be pointed at `http://<host>:47625`
+pub const DEFAULT_PORT: u16 = 47625;
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(200);
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+// Connections beyond this are turned away with 503 instead of spawning a thread each
+const MAX_CONNECTIONS: usize = 8;
+// Requests are a single short GET, so anything larger is not a client of ours
+const MAX_REQUEST_SIZE: u64 = 16 * 1024;
+
+// Serves the verified download cache to other installers on the LAN as
+// `GET /sha1/<sha1>`. Only files already in the cache are served, and
+// clients verify what they receive like any other download.
+pub struct CacheServer {
+    address: SocketAddr,
+    stopped: Arc<AtomicBool>,
+}
+
+impl CacheServer {
+    // Listens only on `ip`, the interface the user chose to share on
+    pub fn start(cache: DownloadCache, ip: IpAddr, port: u16) -> Result<Self> {
+        let listener = TcpListener::bind((ip, port))
+            .with_context(|| format!("Failed to listen on {ip} port {port}"))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+        let ip = match ip.is_unspecified() {
+            true => local_ip().unwrap_or([127, 0, 0, 1].into()),
+            false => ip,
+        };
+        let address = SocketAddr::new(ip, port);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stopped_flag = stopped.clone();
+        let connections = Arc::new(AtomicUsize::new(0));
+        thread::spawn(move || {
+            while !stopped_flag.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((mut stream, peer)) => {
+                        if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                            connections.fetch_sub(1, Ordering::SeqCst);
+                            log::warn!("Too many connections, turning away {peer}");
+                            let _ = stream.write_all(
+                                b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                            );
+                            continue;
+                        }
+                        let cache = cache.clone();
+                        let connections = connections.clone();
+                        thread::spawn(move || {
+                            if let Err(e) = handle_connection(stream, &cache) {
+                                log::warn!("Failed to serve cache to {peer}: {e:?}");
+                            }
+                            connections.fetch_sub(1, Ordering::SeqCst);
+                        });
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_INTERVAL);
+                    }
+                    Err(e) => log::warn!("Failed to accept connection: {e:?}"),
+                }
+            }
+            log::info!("Stopped serving download cache.");
+        });
+        log::info!("Serving download cache at http://{address}");
+        Ok(Self { address, stopped })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for CacheServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn handle_connection(stream: TcpStream, cache: &DownloadCache) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?.take(MAX_REQUEST_SIZE));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Headers are not used, but are read up to the empty line so the client sees a
+    // clean response
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+    let mut stream = stream;
+    let sha1 = match request_line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["GET", path, _] => path
+            .strip_prefix("/sha1/")
+            .filter(|sha1| sha1.len() == 40 && sha1.chars().all(|c| c.is_ascii_hexdigit())),
+        _ => None,
+    };
+    let Some(path) = sha1.and_then(|sha1| cache.find(sha1)) else {
+        stream.write_all(
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        )?;
+        return Ok(());
+    };
+    let mut file =
+        File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let size = file.metadata()?.len();
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().replace('"', ""))
+        .unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {size}\r\nContent-Disposition: attachment; filename=\"{file_name}\"\r\nConnection: close\r\n\r\n"
+    )?;
+    io::copy(&mut file, &mut stream)?;
+    stream.flush()?;
+    log::info!("Served {} to LAN peer", path.display());
+    Ok(())
+}
+
+// The address other machines reach this one at. Connecting a UDP socket
+// sends nothing; it only selects the outgoing interface.
+pub fn local_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("192.0.2.1", 80)).ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
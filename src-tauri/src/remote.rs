@@ -0,0 +1,226 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use reqwest::{Client, StatusCode};
+use semver::Version;
+
+use crate::config::ModPackConfig;
+use crate::network::{self, HostAllowlist};
+use crate::signature::{self, SignaturePolicy, SIGNATURE_EXTENSION};
+use crate::state::InstallerState;
+
+// Lets an installer shipped without config.yaml find its pack
+const DEFAULT_UPDATE_URL: Option<&str> = option_env!("MM_INSTALLER_UPDATE_URL");
+// Replaced configs, kept for diagnostics
+const HISTORY_DIR: &str = "config-history";
+
+pub struct RemoteConfig;
+
+impl RemoteConfig {
+    // Replaces config.yaml with the one published on the player's channel when
+    // its pack version is newer. Returns the new pack version.
+    pub async fn update(
+        config_path: &Path,
+        state_path: &Path,
+        app_dir: &Path,
+    ) -> Result<Option<Version>> {
+        let channel = if state_path.exists() {
+            InstallerState::load(state_path)?
+                .get_channel()
+                .map(str::to_string)
+        } else {
+            None
+        };
+        Self::fetch(config_path, app_dir, channel.as_deref(), false).await
+    }
+
+    // Replaces config.yaml with the one published on `channel` even if it is
+    // older, then records the channel so that Update installs it
+    pub async fn switch_channel(
+        config_path: &Path,
+        state_path: &Path,
+        app_dir: &Path,
+        channel: &str,
+    ) -> Result<Option<Version>> {
+        let config = ModPackConfig::load_from_path(config_path)?;
+        let channels = config.get_channels();
+        if !channels.iter().any(|c| c.id == channel) {
+            bail!("Unknown channel '{channel}'");
+        }
+        let mut state = InstallerState::load(state_path)?;
+        if state.get_process_mode().is_some() {
+            bail!("Finish the interrupted installation before switching channels.");
+        }
+        // Installs made before channels were recorded are on the default channel,
+        // so picking it again does not count as a change
+        state.init_channel(channels.first().map(|c| c.id.clone()));
+        let version = Self::fetch(config_path, app_dir, Some(channel), true).await?;
+        state.set_channel(Some(channel.to_string()));
+        state.save(state_path)?;
+        log::info!("Switched to channel '{channel}'");
+        Ok(version)
+    }
+
+    async fn fetch(
+        config_path: &Path,
+        app_dir: &Path,
+        channel: Option<&str>,
+        allow_downgrade: bool,
+    ) -> Result<Option<Version>> {
+        // A local config that fails verification still gives the update URL, as the
+        // fetched config is verified on its own, and is then always replaced
+        let (local, is_local_verified) = if config_path.exists() {
+            let raw = fs::read_to_string(config_path)
+                .with_context(|| format!("Failed to read {}", config_path.display()))?;
+            let is_verified = signature::verify_file(config_path, raw.as_bytes())
+                .inspect_err(|e| log::warn!("Local config is not trusted: {e:?}"))
+                .is_ok();
+            (Some(ModPackConfig::load_from_str(&raw)?), is_verified)
+        } else {
+            (None, true)
+        };
+        let Some(url) = local
+            .as_ref()
+            .and_then(|config| config.get_update_url(channel))
+            .or(DEFAULT_UPDATE_URL.filter(|url| !url.is_empty()))
+        else {
+            return Ok(None);
+        };
+        let allowlist = local
+            .as_ref()
+            .map_or_else(|| HostAllowlist::new(&[]), ModPackConfig::get_allowed_hosts);
+        allowlist.check_str(url)?;
+        let client = network::client_builder()?
+            .redirect(allowlist.redirect_policy())
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        let Some(raw) = fetch_text(&client, url).await? else {
+            bail!("No config is published at {url}");
+        };
+        let signature = fetch_text(&client, &format!("{url}.{SIGNATURE_EXTENSION}")).await?;
+        signature::check(
+            url,
+            raw.as_bytes(),
+            signature.as_deref(),
+            SignaturePolicy::current(),
+        )?;
+        let remote = ModPackConfig::load_from_str(&raw)
+            .with_context(|| format!("Invalid config at {url}"))?;
+        let remote_version = remote.get_pack_version().clone();
+        if let Some(local) = &local {
+            let local_version = local.get_pack_version();
+            let is_outdated = if allow_downgrade {
+                &remote_version != local_version
+            } else {
+                &remote_version > local_version
+            };
+            if !is_outdated && is_local_verified {
+                log::info!("Pack {local_version} is up to date.");
+                return Ok(None);
+            }
+            archive_config(config_path, local_version, app_dir)?;
+        }
+        replace_config(config_path, &raw, signature.as_deref())?;
+        log::info!("Downloaded pack {remote_version} from {url}");
+        Ok(Some(remote_version))
+    }
+}
+
+// Returns None for 404 so that a missing signature can be handled by the policy
+async fn fetch_text(client: &Client, url: &str) -> Result<Option<String>> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to request {url}"))?;
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        bail!("Request to {url} failed with status {status}.");
+    }
+    let text = response
+        .text()
+        .await
+        .with_context(|| format!("Failed to read {url}"))?;
+    Ok(Some(text))
+}
+
+fn archive_config(config_path: &Path, version: &Version, app_dir: &Path) -> Result<()> {
+    let history_dir = app_dir.join(HISTORY_DIR);
+    fs::create_dir_all(&history_dir)
+        .with_context(|| format!("Failed to create {}", history_dir.display()))?;
+    let archived_path = history_dir.join(format!("config-{version}.yaml"));
+    fs::copy(config_path, &archived_path)
+        .with_context(|| format!("Failed to archive {}", config_path.display()))?;
+    let signature_path = signature::signature_path(config_path);
+    if signature_path.exists() {
+        fs::copy(&signature_path, signature::signature_path(&archived_path))
+            .with_context(|| format!("Failed to archive {}", signature_path.display()))?;
+    }
+    Ok(())
+}
+
+// Written beside the target and renamed so that a failed write keeps the old config
+fn replace_config(config_path: &Path, raw: &str, signature: Option<&str>) -> Result<()> {
+    let signature_path = signature::signature_path(config_path);
+    match signature {
+        Some(signature) => write_file(&signature_path, signature)?,
+        None if signature_path.exists() => fs::remove_file(&signature_path)
+            .with_context(|| format!("Failed to remove {}", signature_path.display()))?,
+        None => {}
+    }
+    write_file(config_path, raw)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, content)
+        .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+    fs::rename(&temp_path, path).with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    const CONFIG: &str = r#"
+schemaVersion: 4
+packVersion: "1.0.0"
+profile:
+  name: Test
+  icon: ""
+  version: neoforge-21.1.215
+modLoader:
+  name: NeoForge
+  url: https://github.com/example/loader/releases/download/v1/installer.jar
+  hash: 0000000000000000000000000000000000000000
+channels:
+  - id: stable
+    name: Stable
+    updateUrl: https://github.com/example/pack/releases/download/stable/config.yaml
+"#;
+
+    #[tokio::test]
+    async fn rejects_unknown_channels() {
+        let dir = env::temp_dir().join(format!("remote-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.yaml");
+        let state_path = dir.join("state.json");
+        fs::write(&config_path, CONFIG).unwrap();
+        InstallerState::new(&Version::new(1, 0, 0), &Version::new(1, 0, 0))
+            .save(&state_path)
+            .unwrap();
+
+        let result = RemoteConfig::switch_channel(&config_path, &state_path, &dir, "nightly").await;
+        let state = InstallerState::load(&state_path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(format!("{:#}", result.unwrap_err()).contains("Unknown channel"));
+        assert_eq!(state.get_channel(), None);
+    }
+}
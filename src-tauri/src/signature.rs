@@ -0,0 +1,265 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use blake2::{Blake2b512, Digest};
+use ring::signature::{UnparsedPublicKey, ED25519};
+
+// Detached minisign signatures next to the signed file
+pub const SIGNATURE_EXTENSION: &str = "minisig";
+// Legacy (`minisign -S -l`) signatures over the file itself, and the default
+// pre-hashed ones over its BLAKE2b-512 hash
+const ALGORITHM_ED25519: &[u8; 2] = b"Ed";
+const ALGORITHM_PREHASHED: &[u8; 2] = b"ED";
+const TRUSTED_COMMENT_PREFIX: &str = "trusted comment: ";
+
+// Minisign public keys embedded at build time, comma separated
+const TRUSTED_KEYS: Option<&str> = option_env!("MM_INSTALLER_TRUSTED_KEYS");
+// `require`, `warn` or `off`. Defaults to `require` when keys are embedded.
+const POLICY: Option<&str> = option_env!("MM_INSTALLER_SIGNATURE_POLICY");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignaturePolicy {
+    Off,
+    Warn,
+    Require,
+}
+
+impl SignaturePolicy {
+    pub fn current() -> Self {
+        match POLICY.map(str::trim) {
+            Some("require") => Self::Require,
+            Some("warn") => Self::Warn,
+            Some("off") => Self::Off,
+            _ if TRUSTED_KEYS.is_some_and(|keys| !keys.trim().is_empty()) => Self::Require,
+            _ => Self::Off,
+        }
+    }
+}
+
+struct PublicKey {
+    key_id: [u8; 8],
+    key: [u8; 32],
+}
+
+impl PublicKey {
+    // Accepts the base64 line of a minisign public key, or the whole .pub file
+    fn parse(text: &str) -> Result<Self> {
+        let line = text
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+            .context("Public key is empty")?;
+        let bytes = STANDARD
+            .decode(line)
+            .context("Public key is not valid base64")?;
+        if bytes.len() != 42 || &bytes[..2] != ALGORITHM_ED25519 {
+            bail!("Not a minisign Ed25519 public key");
+        }
+        Ok(Self {
+            key_id: bytes[2..10].try_into()?,
+            key: bytes[10..].try_into()?,
+        })
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        UnparsedPublicKey::new(&ED25519, self.key)
+            .verify(message, signature)
+            .is_ok()
+    }
+}
+
+fn trusted_keys() -> Vec<PublicKey> {
+    TRUSTED_KEYS
+        .unwrap_or_default()
+        .split(',')
+        .filter(|key| !key.trim().is_empty())
+        .filter_map(|key| {
+            PublicKey::parse(key)
+                .inspect_err(|e| log::error!("Invalid embedded public key: {e:?}"))
+                .ok()
+        })
+        .collect()
+}
+
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{SIGNATURE_EXTENSION}"));
+    path.with_file_name(file_name)
+}
+
+// Checks `<path>.minisig` according to the build-time policy
+pub fn verify_file(path: &Path, content: &[u8]) -> Result<()> {
+    let policy = SignaturePolicy::current();
+    if policy == SignaturePolicy::Off {
+        return Ok(());
+    }
+    let signature_path = signature_path(path);
+    let signature = if signature_path.exists() {
+        Some(
+            fs::read_to_string(&signature_path)
+                .with_context(|| format!("Failed to read {}", signature_path.display()))?,
+        )
+    } else {
+        None
+    };
+    check(
+        &path.display().to_string(),
+        content,
+        signature.as_deref(),
+        policy,
+    )
+}
+
+// Returns an error under the `require` policy and logs a warning under `warn`
+pub fn check(
+    name: &str,
+    content: &[u8],
+    signature: Option<&str>,
+    policy: SignaturePolicy,
+) -> Result<()> {
+    if policy == SignaturePolicy::Off {
+        return Ok(());
+    }
+    let result = match signature {
+        Some(signature) => verify(content, signature, &trusted_keys())
+            .with_context(|| format!("Signature of {name} is invalid")),
+        None => Err(anyhow::anyhow!("{name} is not signed")),
+    };
+    match (result, policy) {
+        (Ok(trusted_comment), _) => {
+            log::info!("Verified signature of {name}: {trusted_comment}");
+            Ok(())
+        }
+        (Err(e), SignaturePolicy::Require) => Err(e),
+        (Err(e), _) => {
+            log::warn!("{e:?}");
+            Ok(())
+        }
+    }
+}
+
+// Returns the trusted comment of a valid signature
+fn verify(content: &[u8], signature: &str, keys: &[PublicKey]) -> Result<String> {
+    let lines: Vec<&str> = signature.lines().map(str::trim_end).collect();
+    let [_, signature_line, comment_line, global_signature_line, ..] = lines[..] else {
+        bail!("Not a minisign signature");
+    };
+    let signature = STANDARD
+        .decode(signature_line)
+        .context("Signature is not valid base64")?;
+    if signature.len() != 74 {
+        bail!("Not a minisign signature");
+    }
+    let (algorithm, rest) = signature.split_at(2);
+    let (key_id, signature) = rest.split_at(8);
+    let message = if algorithm == ALGORITHM_PREHASHED {
+        Blake2b512::digest(content).to_vec()
+    } else if algorithm == ALGORITHM_ED25519 {
+        content.to_vec()
+    } else {
+        bail!("Unsupported signature algorithm");
+    };
+    let Some(key) = keys.iter().find(|key| key.key_id == key_id) else {
+        bail!("Signed with an untrusted key");
+    };
+    if !key.verify(&message, signature) {
+        bail!("Signature does not match the content");
+    }
+    let trusted_comment = comment_line
+        .strip_prefix(TRUSTED_COMMENT_PREFIX)
+        .context("Signature has no trusted comment")?;
+    let global_signature = STANDARD
+        .decode(global_signature_line)
+        .context("Global signature is not valid base64")?;
+    let signed_comment = [signature, trusted_comment.as_bytes()].concat();
+    if !key.verify(&signed_comment, &global_signature) {
+        bail!("Trusted comment does not match the signature");
+    }
+    Ok(trusted_comment.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUBLIC_KEY: &str = "untrusted comment: minisign public key 0807060504030201
+RWQBAgMEBQYHCAOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4";
+    // Same key ID, different key
+    const OTHER_PUBLIC_KEY: &str = "RWQBAgMEBQYHCHm1Vi6P5lT5QHixEuipi6eQH4U65pW+1+DjkQutBJZk";
+    const CONTENT: &[u8] = b"packVersion: 1.0.0\n";
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RWQBAgMEBQYHCJ3BItMemS0GebumE1EKwkl5rzTtNY3rofKIoGTNSAs02C0XydwZguUHd59JtiWoyW3r4qJJoUB3XA7c9qdM5Qo=
+trusted comment: timestamp:1700000000
+yYVgDr7Iql2h1RvhuhdrE6Psdym17RCzqrRaAz57CQGGiSEQJaNCGttsm3cUJBJVJzHfPL+Zp/FgnRMSZQ2CCw==
+";
+    // What `minisign -S` produces by default
+    const PREHASHED_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQBAgMEBQYHCChf8ZaG4PtadxFoG8s6hHwPF2o14ufYcWjn/bqCRHvwZTHfg4lGFtU8uiBR8uwUj/V09V5Z8V9U169hlAXkPgM=
+trusted comment: timestamp:1700000000
+6OArILYqkM95y2+8xkW+LJNudodB6ps4FGSAqEgYUenbdpnWvjPL+YThNsjgokVi6W6FuadBte2SNpQyEimFDw==
+";
+
+    fn keys(text: &str) -> Vec<PublicKey> {
+        vec![PublicKey::parse(text).unwrap()]
+    }
+
+    #[test]
+    fn accepts_valid_signature() {
+        let trusted_comment = verify(CONTENT, SIGNATURE, &keys(PUBLIC_KEY)).unwrap();
+        assert_eq!(trusted_comment, "timestamp:1700000000");
+    }
+
+    #[test]
+    fn accepts_valid_prehashed_signature() {
+        let trusted_comment = verify(CONTENT, PREHASHED_SIGNATURE, &keys(PUBLIC_KEY)).unwrap();
+        assert_eq!(trusted_comment, "timestamp:1700000000");
+    }
+
+    #[test]
+    fn rejects_modified_content() {
+        assert!(verify(b"packVersion: 9.9.9\n", SIGNATURE, &keys(PUBLIC_KEY)).is_err());
+        assert!(verify(
+            b"packVersion: 9.9.9\n",
+            PREHASHED_SIGNATURE,
+            &keys(PUBLIC_KEY)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn rejects_modified_trusted_comment() {
+        let signature = SIGNATURE.replace("1700000000", "1800000000");
+        assert!(verify(CONTENT, &signature, &keys(PUBLIC_KEY)).is_err());
+    }
+
+    #[test]
+    fn rejects_untrusted_keys() {
+        assert!(verify(CONTENT, SIGNATURE, &keys(OTHER_PUBLIC_KEY)).is_err());
+        assert!(verify(CONTENT, SIGNATURE, &[]).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_signatures() {
+        assert!(verify(CONTENT, "", &keys(PUBLIC_KEY)).is_err());
+        // A legacy signature relabeled as pre-hashed
+        let prehashed = SIGNATURE.replacen("RWQB", "RUQB", 1);
+        assert!(verify(CONTENT, &prehashed, &keys(PUBLIC_KEY)).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_public_keys() {
+        assert!(PublicKey::parse("").is_err());
+        assert!(PublicKey::parse("not base64!").is_err());
+        assert!(PublicKey::parse("RWQBAgMEBQYHCA==").is_err());
+    }
+
+    #[test]
+    fn ignores_missing_signature_when_off() {
+        assert!(check("config.yaml", CONTENT, None, SignaturePolicy::Off).is_ok());
+        assert!(check("config.yaml", CONTENT, None, SignaturePolicy::Warn).is_ok());
+        assert!(check("config.yaml", CONTENT, None, SignaturePolicy::Require).is_err());
+    }
+}
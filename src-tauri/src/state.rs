@@ -1,11 +1,17 @@
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::Path,
+};
 
 use anyhow::{Context, Result};
 use semver::Version;
 use serde::{Deserialize, Serialize};
 
-use crate::config::{ModEntry, ModLoader, ResourceEntry, SourceType};
+use crate::config::{ModEntry, ModLoader, ResourceEntry, Selection, SourceType};
 use crate::installer::InstallerMode;
+use crate::jar::ModMetadata;
+use crate::maven::MavenArtifact;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -19,8 +25,19 @@ pub struct InstallerState {
     mods: Vec<ModState>,
     #[serde(default)]
     resources: Vec<ResourceState>,
+    #[serde(flatten)]
+    selection: Selection,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pending_disabled_mods: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    manual_downloads: Vec<ManualDownload>,
     #[serde(skip_serializing_if = "Option::is_none")]
     process_mode: Option<InstallerMode>,
+    // Release channel chosen by the player, and the one the installed files are from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    channel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    installed_channel: Option<String>,
 
     #[serde(skip)]
     mod_index: HashMap<String, usize>,
@@ -36,7 +53,12 @@ impl InstallerState {
             mod_loader: None,
             mods: Vec::new(),
             resources: Vec::new(),
+            selection: Selection::default(),
+            pending_disabled_mods: BTreeSet::new(),
+            manual_downloads: Vec::new(),
             process_mode: None,
+            channel: None,
+            installed_channel: None,
             mod_index: HashMap::new(),
             resource_index: HashMap::new(),
         }
@@ -54,6 +76,34 @@ impl InstallerState {
         self.pack_version = version.clone();
     }
 
+    pub fn get_selection(&self) -> &Selection {
+        &self.selection
+    }
+
+    pub fn set_selection(&mut self, selection: Selection) {
+        self.selection = selection;
+    }
+
+    pub fn get_channel(&self) -> Option<&str> {
+        self.channel.as_deref()
+    }
+
+    pub fn set_channel(&mut self, channel: Option<String>) {
+        self.channel = channel;
+    }
+
+    // Records `channel` as both chosen and installed when neither is recorded yet
+    pub fn init_channel(&mut self, channel: Option<String>) {
+        if self.channel.is_none() && self.installed_channel.is_none() {
+            self.installed_channel = channel.clone();
+            self.channel = channel;
+        }
+    }
+
+    pub fn is_channel_changed(&self) -> bool {
+        self.channel != self.installed_channel
+    }
+
     pub fn get_process_mode(&self) -> Option<InstallerMode> {
         self.process_mode
     }
@@ -62,33 +112,13 @@ impl InstallerState {
         self.process_mode = Some(mode);
     }
 
+    // Same keys as the config, so entries are matched whatever their source type
     fn mod_key(source: &SourceType) -> String {
-        match source {
-            SourceType::Curseforge {
-                project_id,
-                file_id,
-            } => format!("cf:{project_id}:{file_id}"),
-            SourceType::Modrinth {
-                project_id,
-                version_id,
-            } => format!("mr:{project_id}:{version_id}"),
-            SourceType::Direct { url } => format!("direct:{url}"),
-        }
+        source.key()
     }
 
     fn resource_key(source: &SourceType, target_dir: &str) -> (String, String) {
-        let source_key = match source {
-            SourceType::Curseforge {
-                project_id,
-                file_id,
-            } => format!("cf:{project_id}:{file_id}"),
-            SourceType::Modrinth {
-                project_id,
-                version_id,
-            } => format!("mr:{project_id}:{version_id}"),
-            SourceType::Direct { url } => format!("direct:{url}"),
-        };
-        (source_key, target_dir.to_string())
+        (source.key(), target_dir.to_string())
     }
 
     pub fn get_mod_loader(&self) -> Option<&ModLoaderState> {
@@ -114,6 +144,10 @@ impl InstallerState {
 
     pub fn add_mod(&mut self, mod_state: ModState) {
         let key = Self::mod_key(&mod_state.source);
+        if let Some(&index) = self.mod_index.get(&key) {
+            self.mods[index] = mod_state;
+            return;
+        }
         let index = self.mods.len();
         self.mods.push(mod_state);
         self.mod_index.insert(key, index);
@@ -136,6 +170,18 @@ impl InstallerState {
         }
     }
 
+    // Remembers a disabled mod removed during Update so that its successor stays disabled
+    pub fn add_pending_disabled_mod(&mut self, mod_state: &ModState) {
+        self.pending_disabled_mods
+            .extend(mod_identity_keys(&mod_state.name, &mod_state.source));
+    }
+
+    pub fn is_pending_disabled_mod(&self, mod_entry: &ModEntry) -> bool {
+        mod_identity_keys(&mod_entry.name, &mod_entry.source)
+            .iter()
+            .any(|key| self.pending_disabled_mods.contains(key))
+    }
+
     pub fn get_resource(&self, resource_entry: &ResourceEntry) -> Option<&ResourceState> {
         let key = Self::resource_key(&resource_entry.source, &resource_entry.target_dir);
         self.resource_index
@@ -143,13 +189,56 @@ impl InstallerState {
             .map(|&index| &self.resources[index])
     }
 
+    pub fn get_all_resources(&self) -> &Vec<ResourceState> {
+        &self.resources
+    }
+
     pub fn add_resource(&mut self, resource_state: ResourceState) {
         let key = Self::resource_key(&resource_state.source, &resource_state.target_dir);
+        if let Some(&index) = self.resource_index.get(&key) {
+            self.resources[index] = resource_state;
+            return;
+        }
         let index = self.resources.len();
         self.resources.push(resource_state);
         self.resource_index.insert(key, index);
     }
 
+    // Files that the player has to download manually before the process can finish
+    pub fn get_manual_downloads(&self) -> &Vec<ManualDownload> {
+        &self.manual_downloads
+    }
+
+    pub fn add_manual_download(&mut self, manual_download: ManualDownload) {
+        self.manual_downloads.retain(|m| {
+            m.source != manual_download.source || m.target_dir != manual_download.target_dir
+        });
+        self.manual_downloads.push(manual_download);
+    }
+
+    pub fn remove_manual_download(&mut self, manual_download: &ManualDownload) {
+        self.manual_downloads.retain(|m| {
+            m.source != manual_download.source || m.target_dir != manual_download.target_dir
+        });
+    }
+
+    pub fn remove_resource(&mut self, resource_state: &ResourceState) {
+        let key = Self::resource_key(&resource_state.source, &resource_state.target_dir);
+        let Some(&index) = self.resource_index.get(&key) else {
+            log::warn!(
+                "Attempted to remove resource that doesn't exist in state: {}",
+                resource_state.file_name
+            );
+            return;
+        };
+        self.resources.remove(index);
+        self.resource_index.remove(&key);
+        for i in index..self.resources.len() {
+            let key = Self::resource_key(&self.resources[i].source, &self.resources[i].target_dir);
+            self.resource_index.insert(key, i);
+        }
+    }
+
     pub fn load(path: &Path) -> Result<Self> {
         let raw = fs::read_to_string(path)
             .with_context(|| format!("Failed to read installer state at {}", path.display()))?;
@@ -182,6 +271,8 @@ impl InstallerState {
 
     pub fn finalize(&mut self, path: &Path) -> Result<()> {
         self.process_mode = None;
+        self.pending_disabled_mods.clear();
+        self.installed_channel = self.channel.clone();
         self.save(path)?;
         Ok(())
     }
@@ -202,22 +293,84 @@ pub struct ModLoaderState {
 
 impl ModLoaderState {
     pub fn equals(&self, config: &ModLoader) -> bool {
-        self.url == config.url && self.hash == config.hash
+        config.source.get_url().is_ok_and(|url| self.url == url) && self.hash == config.hash
     }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModState {
+    #[serde(default)]
+    pub name: String,
     pub file_name: String,
     #[serde(flatten)]
     pub source: SourceType,
     pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metadata: Vec<ModMetadata>,
 }
 
 impl ModState {
     pub fn equals(&self, config: &ModEntry, is_ignore_hash: bool) -> bool {
-        self.source == config.source && (is_ignore_hash || self.hash == config.hash)
+        self.source == config.source
+            && (is_ignore_hash
+                || (self.hash == config.hash && self.fingerprint == config.fingerprint))
+    }
+}
+
+// Keys shared by different versions of the same mod
+fn mod_identity_keys(name: &str, source: &SourceType) -> Vec<String> {
+    let mut keys = Vec::new();
+    if !name.is_empty() {
+        keys.push(format!("name:{name}"));
+    }
+    match source {
+        SourceType::Curseforge { project_id, .. } => keys.push(format!("cf:{project_id}")),
+        SourceType::Modrinth { project_id, .. } => keys.push(format!("mr:{project_id}")),
+        SourceType::Maven { coordinates, .. } => {
+            if let Ok(artifact) = MavenArtifact::parse(coordinates) {
+                keys.push(format!("maven:{}:{}", artifact.group, artifact.artifact));
+            }
+        }
+        SourceType::Github { .. } | SourceType::Local { .. } | SourceType::Direct { .. } => {}
+    }
+    keys
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManualDownload {
+    pub name: String,
+    pub file_name: String,
+    #[serde(flatten)]
+    pub source: SourceType,
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<u32>,
+    // SHA-1 published by CurseForge, used when config.yaml has no hash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_sha1: Option<String>,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub website_url: Option<String>,
+    // Set for resources, mods go to mods/
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_dir: Option<String>,
+    #[serde(default)]
+    pub decompress: bool,
+}
+
+impl ManualDownload {
+    // A file matches when its SHA-1 and, if given, its fingerprint match.
+    // A fingerprint alone is too weak to identify the file.
+    pub fn matches(&self, sha1: &str, fingerprint: u32) -> bool {
+        let expected_sha1 = Some(self.hash.as_str())
+            .filter(|hash| !hash.is_empty())
+            .or(self.published_sha1.as_deref());
+        expected_sha1.is_some_and(|expected| expected.eq_ignore_ascii_case(sha1))
+            && self.fingerprint.map_or(true, |f| f == fingerprint)
     }
 }
 
@@ -228,12 +381,111 @@ pub struct ResourceState {
     #[serde(flatten)]
     pub source: SourceType,
     pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<u32>,
     pub target_dir: String,
     pub decompress: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub world_copies: Vec<String>,
 }
 
 impl ResourceState {
     pub fn equals(&self, config: &ResourceEntry) -> bool {
-        self.source == config.source && self.hash == config.hash
+        self.source == config.source
+            && self.hash == config.hash
+            && self.fingerprint == config.fingerprint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::config::Side;
+
+    fn modrinth(project_id: &str, version_id: &str) -> SourceType {
+        SourceType::Modrinth {
+            project_id: project_id.to_string(),
+            version_id: version_id.to_string(),
+        }
+    }
+
+    fn mod_entry(name: &str, source: SourceType) -> ModEntry {
+        ModEntry {
+            name: name.to_string(),
+            source,
+            hash: "1".repeat(40),
+            fingerprint: None,
+            side: Side::Both,
+            optional: false,
+            default: false,
+            group: None,
+            os: None,
+            arch: None,
+            variants: None,
+        }
+    }
+
+    fn mod_state(name: &str, source: SourceType) -> ModState {
+        ModState {
+            name: name.to_string(),
+            file_name: format!("{name}.jar"),
+            source,
+            hash: "1".repeat(40),
+            fingerprint: None,
+            metadata: Vec::new(),
+        }
+    }
+
+    // A disabled Sodium 1 removed during Update, to be replaced by Sodium 2
+    fn updating_state() -> InstallerState {
+        let mut state = InstallerState::new(&Version::new(1, 0, 0), &Version::new(1, 0, 0));
+        let old_mod = mod_state("Sodium", modrinth("sodium", "v1"));
+        state.add_mod(old_mod.clone());
+        state.add_pending_disabled_mod(&old_mod);
+        state.remove_mod(&old_mod);
+        state
+    }
+
+    #[test]
+    fn keeps_new_version_of_disabled_mod_disabled() {
+        let state = updating_state();
+        assert!(state.is_pending_disabled_mod(&mod_entry("Sodium", modrinth("sodium", "v2"))));
+        // Matched by name when the source changes
+        let github = SourceType::Github {
+            repo: "example/sodium".to_string(),
+            tag: "v2".to_string(),
+            asset: "sodium.jar".to_string(),
+        };
+        assert!(state.is_pending_disabled_mod(&mod_entry("Sodium", github)));
+        assert!(!state.is_pending_disabled_mod(&mod_entry("Iris", modrinth("iris", "v1"))));
+    }
+
+    #[test]
+    fn keeps_disabled_mods_until_update_finishes() {
+        let path = env::temp_dir().join(format!("state-{}.json", uuid::Uuid::new_v4()));
+        let new_entry = mod_entry("Sodium", modrinth("sodium", "v2"));
+        updating_state().save(&path).unwrap();
+        // An interrupted Update still knows the mod was disabled
+        let mut state = InstallerState::load(&path).unwrap();
+        assert!(state.is_pending_disabled_mod(&new_entry));
+        state.finalize(&path).unwrap();
+        let state = InstallerState::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(!state.is_pending_disabled_mod(&new_entry));
+    }
+
+    #[test]
+    fn treats_unrecorded_channel_as_default() {
+        let mut state = InstallerState::new(&Version::new(1, 0, 0), &Version::new(1, 0, 0));
+        state.init_channel(Some("stable".to_string()));
+        state.set_channel(Some("stable".to_string()));
+        assert!(!state.is_channel_changed());
+        state.set_channel(Some("beta".to_string()));
+        assert!(state.is_channel_changed());
+        // Recorded channels are kept
+        state.init_channel(Some("stable".to_string()));
+        assert_eq!(state.get_channel(), Some("beta"));
     }
 }
@@ -17,10 +17,15 @@ import TitleBar from "./components/TitleBar";
 import InstallerScreen, {
   InstallerMode,
 } from "./components/screens/InstallerScreen";
+import ManualDownloadScreen from "./components/screens/ManualDownloadScreen";
+import OptionsScreen, {
+  OptionsInfo,
+  Selection,
+} from "./components/screens/OptionsScreen";
 import TitleScreen from "./components/screens/TitleScreen";
 import { Language, translations } from "./utils/localizer";
 
-export type Screen = "title" | "installer";
+export type Screen = "title" | "options" | "installer" | "manualDownload";
 
 type ModeResult = {
   isAccept: boolean;
@@ -44,27 +49,73 @@ export default function App() {
   const [installerMode, setInstallerMode] = useState<InstallerMode | null>(
     null
   );
+  const [optionsInfo, setOptionsInfo] = useState<OptionsInfo | null>(null);
   const [errorMessage, setErrorMessage] = useState<string | null>(null);
 
   const translation = translations[language];
   const titleLabel = screen === "title" ? "" : translation.installerTitle;
 
+  const startMode = async (
+    mode: InstallerMode,
+    selection: Selection | null
+  ) => {
+    const result = await invoke<ModeResult>("select_mode", {
+      mode,
+      selection,
+    });
+    if (!result.isAccept) {
+      setErrorMessage(
+        translation.occurredError + (result.error || "<unknown error>")
+      );
+      return;
+    }
+    setInstallerMode(mode);
+    setScreen("installer");
+  };
+
+  const showOptions = async (mode: InstallerMode) => {
+    try {
+      const info = await invoke<OptionsInfo>("get_options");
+      if (info.variants.length === 0 && info.choices.length === 0) {
+        await startMode(mode, null);
+        return;
+      }
+      setOptionsInfo(info);
+      setInstallerMode(mode);
+      setScreen("options");
+    } catch (e: unknown) {
+      setErrorMessage(translation.occurredError + String(e));
+    }
+  };
+
   const getScreen = () => {
     switch (screen) {
       case "title":
         return (
           <TitleScreen
             onModeSelect={async (mode) => {
-              const result = await invoke<ModeResult>("select_mode", { mode });
-              if (!result.isAccept) {
-                setErrorMessage(
-                  translation.occurredError +
-                    (result.error || "<unknown error>")
-                );
-                return;
+              if (mode === "install") {
+                await showOptions(mode);
+              } else {
+                await startMode(mode, null);
               }
-              setInstallerMode(mode);
-              setScreen("installer");
+            }}
+            onChangeOptions={async () => {
+              await showOptions("update");
+            }}
+            translation={translation}
+          />
+        );
+      case "options":
+        return (
+          <OptionsScreen
+            info={optionsInfo!}
+            onConfirm={async (selection) => {
+              await startMode(installerMode!, selection);
+            }}
+            onCancel={() => {
+              setInstallerMode(null);
+              setScreen("title");
             }}
             translation={translation}
           />
@@ -80,6 +131,20 @@ export default function App() {
               setInstallerMode(null);
               setScreen("title");
             }}
+            onManualDownload={() => setScreen("manualDownload")}
+            translation={translation}
+          />
+        );
+      case "manualDownload":
+        return (
+          <ManualDownloadScreen
+            onContinue={async () => {
+              await startMode(installerMode!, null);
+            }}
+            onCancel={() => {
+              setInstallerMode(null);
+              setScreen("title");
+            }}
             translation={translation}
           />
         );
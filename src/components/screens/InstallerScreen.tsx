@@ -46,17 +46,24 @@ type InstallerEvent =
       type: "addAlert";
       level: "info" | "warning";
       translation_key: string;
+      detail?: string;
+      duplicate_mod?: string;
+    }
+  | {
+      type: "requireManualDownload";
     };
 
 type AlertInfo = {
   level: AlertProps["severity"];
   message: string;
+  duplicateMod?: string;
 };
 
 type InstallerScreenProps = {
   mode: InstallerMode;
   onComplete: () => void;
   onDismissError: () => void;
+  onManualDownload: () => void;
   translation: Translation;
 };
 
@@ -69,6 +76,7 @@ export default function InstallerScreen(props: InstallerScreenProps) {
   const [errorMessage, setErrorMessage] = useState<string | null>(null);
 
   const isInstalling = useRef(false);
+  const requiresManualDownload = useRef(false);
 
   useEffect(() => {
     if (isInstalling.current) {
@@ -121,10 +129,14 @@ export default function InstallerScreen(props: InstallerScreenProps) {
                 message:
                   props.translation[
                     payload.translation_key as keyof Translation
-                  ],
+                  ] + (payload.detail ?? ""),
+                duplicateMod: payload.duplicate_mod,
               },
             ]);
             break;
+          case "requireManualDownload":
+            requiresManualDownload.current = true;
+            break;
         }
       });
       try {
@@ -137,6 +149,10 @@ export default function InstallerScreen(props: InstallerScreenProps) {
         setDetail("");
         setIsFinished(true);
       } catch (e: unknown) {
+        if (requiresManualDownload.current) {
+          props.onManualDownload();
+          return;
+        }
         setErrorMessage(typeof e === "string" ? e : String(e));
       }
     };
@@ -149,6 +165,26 @@ export default function InstallerScreen(props: InstallerScreenProps) {
     };
   }, [props.mode]);
 
+  const removeAlert = (index: number) =>
+    setAlerts((alerts) => alerts.filter((_, i) => i !== index));
+
+  const quarantineMod = async (index: number, fileName: string) => {
+    try {
+      await invoke("quarantine_mod", { fileName });
+      removeAlert(index);
+    } catch (e: unknown) {
+      setAlerts((alerts) => [
+        ...alerts,
+        {
+          level: "error",
+          message:
+            props.translation.quarantineFailed +
+            (typeof e === "string" ? e : String(e)),
+        },
+      ]);
+    }
+  };
+
   const handleClose = () => {
     setErrorMessage(null);
     props.onDismissError();
@@ -185,8 +221,18 @@ export default function InstallerScreen(props: InstallerScreenProps) {
             key={index}
             severity={alert.level}
             sx={{ width: "100%" }}
-            onClose={() =>
-              setAlerts((alerts) => alerts.filter((_, i) => i !== index))
+            onClose={() => removeAlert(index)}
+            action={
+              alert.duplicateMod &&
+              isFinished && (
+                <Button
+                  color="inherit"
+                  size="small"
+                  onClick={() => quarantineMod(index, alert.duplicateMod!)}
+                >
+                  {props.translation.quarantine}
+                </Button>
+              )
             }
           >
             {alert.message}
@@ -0,0 +1,161 @@
+import CheckCircleIcon from "@mui/icons-material/CheckCircle";
+import {
+  Alert,
+  Button,
+  Container,
+  List,
+  ListItem,
+  ListItemIcon,
+  ListItemText,
+  Stack,
+  TextField,
+  Typography,
+} from "@mui/material";
+
+import { invoke } from "@tauri-apps/api/core";
+import { openUrl } from "@tauri-apps/plugin-opener";
+import { useEffect, useState } from "react";
+import { Translation } from "../../utils/localizer";
+
+export type ManualDownloadInfo = {
+  name: string;
+  fileName: string;
+  websiteUrl?: string;
+};
+
+type ManualDownloadScreenProps = {
+  onContinue: () => void;
+  onCancel: () => void;
+  translation: Translation;
+};
+
+const scanIntervalMs = 2000;
+
+export default function ManualDownloadScreen(props: ManualDownloadScreenProps) {
+  const [downloads, setDownloads] = useState<ManualDownloadInfo[]>([]);
+  const [adopted, setAdopted] = useState<string[]>([]);
+  const [watchDir, setWatchDir] = useState<string>("");
+  const [errorMessage, setErrorMessage] = useState<string | null>(null);
+
+  const toMessage = (e: unknown) => (typeof e === "string" ? e : String(e));
+
+  const addAdopted = (names: string[]) => {
+    if (names.length > 0) {
+      setAdopted((adopted) => [...adopted, ...names]);
+    }
+  };
+
+  useEffect(() => {
+    const initialize = async () => {
+      try {
+        setDownloads(
+          await invoke<ManualDownloadInfo[]>("get_manual_downloads")
+        );
+        setWatchDir((await invoke<string | null>("get_default_watch_dir")) ?? "");
+      } catch (e: unknown) {
+        setErrorMessage(toMessage(e));
+      }
+    };
+    initialize();
+  }, []);
+
+  // Watch the chosen folder for files matching the expected hashes
+  useEffect(() => {
+    if (watchDir === "") {
+      return;
+    }
+    const timer = setInterval(async () => {
+      try {
+        addAdopted(
+          await invoke<string[]>("scan_manual_downloads", { dir: watchDir })
+        );
+      } catch (e: unknown) {
+        setErrorMessage(toMessage(e));
+      }
+    }, scanIntervalMs);
+    return () => clearInterval(timer);
+  }, [watchDir]);
+
+  // The file dialog is opened by the backend, which reads the picked file itself
+  const pickFile = async () => {
+    try {
+      const name = await invoke<string | null>("adopt_manual_download");
+      if (name === null) {
+        return;
+      }
+      addAdopted([name]);
+      setErrorMessage(null);
+    } catch (e: unknown) {
+      setErrorMessage(toMessage(e));
+    }
+  };
+
+  const isCompleted =
+    downloads.length > 0 &&
+    downloads.every((download) => adopted.includes(download.name));
+
+  return (
+    <Container
+      maxWidth="sm"
+      sx={{
+        height: "100%",
+        display: "flex",
+        alignItems: "center",
+        justifyContent: "center",
+      }}
+    >
+      <Stack spacing={3} sx={{ width: "100%", py: 3 }}>
+        <Typography variant="h6">
+          {props.translation.manualDownloadMessage}
+        </Typography>
+        <List dense>
+          {downloads.map((download) => (
+            <ListItem
+              key={`manual-${download.name}`}
+              secondaryAction={
+                download.websiteUrl && (
+                  <Button
+                    size="small"
+                    onClick={() => openUrl(download.websiteUrl!)}
+                  >
+                    {props.translation.openProjectPage}
+                  </Button>
+                )
+              }
+            >
+              <ListItemIcon>
+                <CheckCircleIcon
+                  color={adopted.includes(download.name) ? "success" : "disabled"}
+                />
+              </ListItemIcon>
+              <ListItemText
+                primary={download.name}
+                secondary={download.fileName}
+              />
+            </ListItem>
+          ))}
+        </List>
+        <TextField
+          label={props.translation.watchFolderLabel}
+          value={watchDir}
+          onChange={(e) => setWatchDir(e.target.value)}
+          size="small"
+        />
+        {errorMessage && <Alert severity="error">{errorMessage}</Alert>}
+        <Stack direction="row" spacing={2} justifyContent="flex-end">
+          <Button onClick={props.onCancel}>{props.translation.back}</Button>
+          <Button onClick={pickFile}>
+            {props.translation.pickFile}
+          </Button>
+          <Button
+            variant="contained"
+            disabled={!isCompleted}
+            onClick={props.onContinue}
+          >
+            {props.translation.next}
+          </Button>
+        </Stack>
+      </Stack>
+    </Container>
+  );
+}
@@ -0,0 +1,180 @@
+import {
+  Button,
+  Checkbox,
+  Container,
+  FormControlLabel,
+  FormLabel,
+  Radio,
+  RadioGroup,
+  Stack,
+  Typography,
+} from "@mui/material";
+
+import { useState } from "react";
+import { Translation } from "../../utils/localizer";
+
+export type VariantInfo = {
+  id: string;
+  name: string;
+};
+
+export type OptionGroupInfo = {
+  id: string;
+  name: string;
+  pickOne: boolean;
+};
+
+export type OptionChoice = {
+  name: string;
+  group?: string;
+  default: boolean;
+  selected: boolean;
+  variants?: string[];
+};
+
+export type OptionsInfo = {
+  variants: VariantInfo[];
+  selectedVariant?: string;
+  groups: OptionGroupInfo[];
+  choices: OptionChoice[];
+};
+
+export type OptionSelections = Record<string, boolean>;
+
+export type Selection = {
+  variant: string | null;
+  options: OptionSelections;
+};
+
+type OptionsScreenProps = {
+  info: OptionsInfo;
+  onConfirm: (selection: Selection) => void;
+  onCancel: () => void;
+  translation: Translation;
+};
+
+export default function OptionsScreen(props: OptionsScreenProps) {
+  const [variant, setVariant] = useState<string | null>(
+    props.info.selectedVariant ?? null
+  );
+  const [selections, setSelections] = useState<OptionSelections>(() =>
+    Object.fromEntries(
+      props.info.choices.map((choice) => [choice.name, choice.selected])
+    )
+  );
+
+  const choicesInVariant = props.info.choices.filter(
+    (choice) =>
+      variant == null || !choice.variants || choice.variants.includes(variant)
+  );
+  const ungrouped = choicesInVariant.filter((choice) => !choice.group);
+
+  const setSelected = (name: string, selected: boolean) => {
+    setSelections((selections) => ({ ...selections, [name]: selected }));
+  };
+
+  const selectOne = (group: OptionGroupInfo, name: string) => {
+    setSelections((selections) => {
+      const next = { ...selections };
+      for (const choice of props.info.choices) {
+        if (choice.group === group.id) {
+          next[choice.name] = choice.name === name;
+        }
+      }
+      return next;
+    });
+  };
+
+  const renderCheckboxes = (choices: OptionChoice[]) =>
+    choices.map((choice) => (
+      <FormControlLabel
+        key={`option-${choice.name}`}
+        control={
+          <Checkbox
+            checked={selections[choice.name] ?? choice.default}
+            onChange={(e) => setSelected(choice.name, e.target.checked)}
+          />
+        }
+        label={choice.name}
+      />
+    ));
+
+  return (
+    <Container
+      maxWidth="sm"
+      sx={{
+        height: "100%",
+        display: "flex",
+        alignItems: "center",
+        justifyContent: "center",
+      }}
+    >
+      <Stack spacing={3} sx={{ width: "100%", py: 3 }}>
+        <Typography variant="h6">{props.translation.optionsMessage}</Typography>
+        {props.info.variants.length > 0 && (
+          <Stack>
+            <FormLabel>{props.translation.variantLabel}</FormLabel>
+            <RadioGroup
+              value={variant ?? ""}
+              onChange={(e) => setVariant(e.target.value)}
+            >
+              {props.info.variants.map((variantInfo) => (
+                <FormControlLabel
+                  key={`variant-${variantInfo.id}`}
+                  value={variantInfo.id}
+                  control={<Radio />}
+                  label={variantInfo.name}
+                />
+              ))}
+            </RadioGroup>
+          </Stack>
+        )}
+        {ungrouped.length > 0 && (
+          <Stack>{renderCheckboxes(ungrouped)}</Stack>
+        )}
+        {props.info.groups.map((group) => {
+          const choices = choicesInVariant.filter(
+            (choice) => choice.group === group.id
+          );
+          if (choices.length === 0) {
+            return null;
+          }
+          return (
+            <Stack key={`group-${group.id}`}>
+              <FormLabel>{group.name}</FormLabel>
+              {group.pickOne ? (
+                <RadioGroup
+                  value={
+                    choices.find((choice) => selections[choice.name])?.name ??
+                    ""
+                  }
+                  onChange={(e) => selectOne(group, e.target.value)}
+                >
+                  {choices.map((choice) => (
+                    <FormControlLabel
+                      key={`option-${choice.name}`}
+                      value={choice.name}
+                      control={<Radio />}
+                      label={choice.name}
+                    />
+                  ))}
+                </RadioGroup>
+              ) : (
+                renderCheckboxes(choices)
+              )}
+            </Stack>
+          );
+        })}
+        <Stack direction="row" spacing={2} justifyContent="flex-end">
+          <Button onClick={props.onCancel}>{props.translation.back}</Button>
+          <Button
+            variant="contained"
+            onClick={() => props.onConfirm({ variant, options: selections })}
+          >
+            {props.translation.next}
+          </Button>
+        </Stack>
+      </Stack>
+    </Container>
+  );
+}
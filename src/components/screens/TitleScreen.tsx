@@ -6,7 +6,9 @@ import {
   CircularProgress,
   Container,
   IconButton,
+  MenuItem,
   Stack,
+  TextField,
   Typography,
   useTheme,
 } from "@mui/material";
@@ -16,13 +18,28 @@ import { useEffect, useRef, useState } from "react";
 import { Translation } from "../../utils/localizer";
 import { InstallerMode, installerModes } from "./InstallerScreen";
 
+export type CacheUsage = {
+  dir: string;
+  size: number;
+  fileCount: number;
+  maxSize: number;
+};
+
+export type ChannelsInfo = {
+  channels: { id: string; name: string }[];
+  selected: string | null;
+};
+
 export type TitleStatus = {
   canInstall: boolean;
   canUpdate: boolean;
+  canChangeOptions: boolean;
+  disabledMods: string[];
 };
 
 type TitleScreenProps = {
   onModeSelect: (mode: InstallerMode) => void;
+  onChangeOptions: () => void;
   translation: Translation;
 };
 
@@ -31,14 +48,106 @@ export default function TitleScreen(props: TitleScreenProps) {
     null
   );
 
+  const [cacheUsage, setCacheUsage] = useState<CacheUsage | null>(null);
+  const [downloadedVersion, setDownloadedVersion] = useState<string | null>(
+    null
+  );
+  const [channels, setChannels] = useState<ChannelsInfo | null>(null);
+  const [isSwitchingChannel, setSwitchingChannel] = useState(false);
+  const [cacheServerUrl, setCacheServerUrl] = useState<string | null>(null);
+  const [cacheServerAddress, setCacheServerAddress] = useState("");
+  const [cachePeer, setCachePeer] = useState("");
+  const [cachePeerError, setCachePeerError] = useState<string | null>(null);
+
   const isInitialized = useRef(false);
 
   const theme = useTheme();
 
   const buttonWidth = 36;
   const initialize = async () => {
+    try {
+      setDownloadedVersion(
+        await invoke<string | null>("fetch_remote_config")
+      );
+    } catch (e: unknown) {
+      console.error(e);
+    }
     const status = await invoke<TitleStatus>("initialize_title");
     setInitialized(status);
+    await loadChannels();
+    await loadCacheUsage();
+    setCachePeer((await invoke<string | null>("get_cache_peer")) ?? "");
+    setCacheServerAddress(
+      (await invoke<string | null>("get_local_address")) ?? "127.0.0.1"
+    );
+  };
+
+  const loadChannels = async () => {
+    try {
+      setChannels(await invoke<ChannelsInfo>("get_channels"));
+    } catch (e: unknown) {
+      console.error(e);
+    }
+  };
+
+  const selectChannel = async (channel: string) => {
+    setSwitchingChannel(true);
+    try {
+      const version = await invoke<string | null>("select_channel", {
+        channel,
+      });
+      if (version) {
+        setDownloadedVersion(version);
+      }
+      setInitialized(await invoke<TitleStatus>("initialize_title"));
+      await loadChannels();
+    } catch (e: unknown) {
+      console.error(e);
+    }
+    setSwitchingChannel(false);
+  };
+
+  const loadCacheUsage = async () => {
+    try {
+      setCacheUsage(await invoke<CacheUsage | null>("get_cache_usage"));
+    } catch (e: unknown) {
+      console.error(e);
+    }
+  };
+
+  const clearCache = async () => {
+    try {
+      await invoke("clear_cache");
+    } catch (e: unknown) {
+      console.error(e);
+    }
+    await loadCacheUsage();
+  };
+
+  const toggleCacheServer = async () => {
+    try {
+      if (cacheServerUrl) {
+        await invoke("stop_cache_server");
+        setCacheServerUrl(null);
+      } else {
+        setCacheServerUrl(
+          await invoke<string>("start_cache_server", {
+            address: cacheServerAddress,
+          })
+        );
+      }
+    } catch (e: unknown) {
+      console.error(e);
+    }
+  };
+
+  const saveCachePeer = async () => {
+    try {
+      await invoke("set_cache_peer", { url: cachePeer });
+      setCachePeerError(null);
+    } catch (e: unknown) {
+      setCachePeerError(String(e));
+    }
   };
 
   useEffect(() => {
@@ -108,6 +217,15 @@ export default function TitleScreen(props: TitleScreenProps) {
                     : props.translation.update}
                 </Button>
               ))}
+              {initializedStatus.canChangeOptions && (
+                <Button
+                  variant="outlined"
+                  onClick={props.onChangeOptions}
+                  sx={{ height: buttonWidth }}
+                >
+                  {props.translation.changeOptions}
+                </Button>
+              )}
             </Stack>
             <IconButton
               color="primary"
@@ -123,6 +241,80 @@ export default function TitleScreen(props: TitleScreenProps) {
               <DescriptionIcon />
             </IconButton>
           </Stack>
+          {downloadedVersion && (
+            <Typography color="primary" textAlign="center" variant="body2">
+              {props.translation.packDownloaded}
+              {downloadedVersion}
+            </Typography>
+          )}
+          {channels && channels.channels.length > 1 && (
+            <TextField
+              select
+              size="small"
+              label={props.translation.channelLabel}
+              value={channels.selected ?? ""}
+              disabled={isSwitchingChannel}
+              onChange={(e) => selectChannel(e.target.value)}
+              sx={{ width: "200px" }}
+            >
+              {channels.channels.map((channel) => (
+                <MenuItem key={`channel-${channel.id}`} value={channel.id}>
+                  {channel.name}
+                </MenuItem>
+              ))}
+            </TextField>
+          )}
+          {initializedStatus.disabledMods.length > 0 && (
+            <Typography
+              color="text.secondary"
+              textAlign="center"
+              variant="caption"
+            >
+              {props.translation.disabledMods}
+              {initializedStatus.disabledMods.join(", ")}
+            </Typography>
+          )}
+          {cacheUsage && cacheUsage.fileCount > 0 && (
+            <Stack direction="row" spacing={1} alignItems="center">
+              <Typography color="text.secondary" variant="caption">
+                {props.translation.downloadCache}
+                {formatBytes(cacheUsage.size)} /{" "}
+                {formatBytes(cacheUsage.maxSize)}
+              </Typography>
+              <Button size="small" onClick={clearCache}>
+                {props.translation.clearCache}
+              </Button>
+              <TextField
+                size="small"
+                label={props.translation.cacheServerAddressLabel}
+                value={cacheServerAddress}
+                onChange={(e) => setCacheServerAddress(e.target.value)}
+                disabled={cacheServerUrl !== null}
+                sx={{ width: "160px" }}
+              />
+              <Button size="small" onClick={toggleCacheServer}>
+                {cacheServerUrl
+                  ? props.translation.stopSharingCache
+                  : props.translation.shareCache}
+              </Button>
+            </Stack>
+          )}
+          {cacheServerUrl && (
+            <Typography color="text.secondary" variant="caption">
+              {props.translation.sharingCache}
+              {cacheServerUrl}
+            </Typography>
+          )}
+          <TextField
+            size="small"
+            label={props.translation.cachePeerLabel}
+            value={cachePeer}
+            onChange={(e) => setCachePeer(e.target.value)}
+            onBlur={saveCachePeer}
+            error={cachePeerError !== null}
+            helperText={cachePeerError}
+            sx={{ width: "320px" }}
+          />
         </Stack>
       )}
       <Backdrop
@@ -134,3 +326,14 @@ export default function TitleScreen(props: TitleScreenProps) {
     </Container>
   );
 }
+
+function formatBytes(bytes: number): string {
+  const units = ["B", "KB", "MB", "GB", "TB"];
+  let value = bytes;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit++;
+  }
+  return `${value.toFixed(unit === 0 ? 0 : 1)} ${units[unit]}`;
+}
@@ -4,9 +4,20 @@ export interface Translation {
   alertOnLaunchModLoader: string;
   alertOnFailedAddProfile: string;
   alertOnFailedLaunchModLoader: string;
+  alertOnFailedUpdateProfile: string;
+  alertOnDuplicateMod: string;
+  alertOnIncompatibleMods: string;
   appTitle: string;
+  back: string;
+  cachePeerLabel: string;
+  cacheServerAddressLabel: string;
+  changeOptions: string;
+  channelLabel: string;
+  clearCache: string;
   close: string;
   complete: string;
+  disabledMods: string;
+  downloadCache: string;
   error: string;
   install: string;
   installFailed: string;
@@ -14,8 +25,13 @@ export interface Translation {
   languageOptionEn: string;
   languageOptionJa: string;
   languageSelectionLabel: string;
+  manualDownloadMessage: string;
+  next: string;
   occurredError: string;
   openLogFolder: string;
+  openProjectPage: string;
+  optionsMessage: string;
+  packDownloaded: string;
   phaseAddProfile: string;
   phaseDownloadModLoader: string;
   phaseDownloadMods: string;
@@ -26,9 +42,17 @@ export interface Translation {
   phaseRemoveMods: string;
   phaseStart: string;
   phaseUpdateSettings: string;
+  pickFile: string;
+  quarantine: string;
+  quarantineFailed: string;
+  shareCache: string;
+  sharingCache: string;
+  stopSharingCache: string;
   titleMessage: string;
   update: string;
   updateFailed: string;
+  variantLabel: string;
+  watchFolderLabel: string;
 }
 
 export const translations: Record<Language, Translation> = {
@@ -36,9 +60,20 @@ export const translations: Record<Language, Translation> = {
     alertOnLaunchModLoader: "Modローダーが起動します。'クライアントをインストール/Install Client'にチェックが入っていることを確認の上、続行してください。",
     alertOnFailedAddProfile: "プロファイルの追加に失敗しました。Minecraftランチャーを起動し、手動で追加してください。",
     alertOnFailedLaunchModLoader: "Modローダーの起動に失敗しました。ダウンロードされたModローダーを手動で実行してください。",
+    alertOnFailedUpdateProfile: "プロファイルの更新に失敗しました。Minecraftランチャーでプロファイルの設定を手動で変更してください。",
+    alertOnDuplicateMod: "modsフォルダに同じModが重複しています。ゲームが起動しない場合は追加されたファイルを隔離してください: ",
+    alertOnIncompatibleMods: "次のModはプロファイルのMinecraftまたはModローダーのバージョンに対応していない可能性があります: ",
     appTitle: "Makibania Modpack Installer",
+    back: "戻る",
+    cachePeerLabel: "LAN内のミラー (例: 192.168.0.10:47625)",
+    cacheServerAddressLabel: "共有するアドレス",
+    changeOptions: "オプション変更",
+    channelLabel: "リリースチャンネル",
+    clearCache: "削除",
     close: "閉じる",
     complete: "完了",
+    disabledMods: "無効化されたMod: ",
+    downloadCache: "ダウンロードキャッシュ: ",
     error: "エラー",
     install: "インストール",
     installFailed: "インストールに失敗しました。\n詳細: ",
@@ -46,8 +81,13 @@ export const translations: Record<Language, Translation> = {
     languageOptionEn: "英語",
     languageOptionJa: "日本語",
     languageSelectionLabel: "表示言語",
+    manualDownloadMessage: "次のファイルは自動でダウンロードできません。各ページからダウンロードすると、監視フォルダ内のファイルが自動で取り込まれます。",
+    next: "次へ",
     occurredError: "エラーが発生しました。\n詳細: ",
     openLogFolder: "ログフォルダを開く",
+    openProjectPage: "ページを開く",
+    optionsMessage: "導入するオプションを選択してください。",
+    packDownloaded: "新しいバージョンのModpackを取得しました: ",
     phaseAddProfile: "プロファイルを追加中...",
     phaseDownloadModLoader: "Modローダーをダウンロード中...",
     phaseDownloadMods: "Modをダウンロード中...",
@@ -58,17 +98,36 @@ export const translations: Record<Language, Translation> = {
     phaseRemoveMods: "不要なModを削除中...",
     phaseStart: "インストールを開始しています...",
     phaseUpdateSettings: "設定を更新中...",
+    pickFile: "ファイルを選択",
+    quarantine: "隔離",
+    quarantineFailed: "Modの隔離に失敗しました。詳細: ",
+    shareCache: "LANで共有",
+    sharingCache: "LANで共有中: ",
+    stopSharingCache: "共有を停止",
     titleMessage: "実行するモードを選択してください。",
     update: "アップデート",
     updateFailed: "アップデートに失敗しました。\n詳細: ",
+    variantLabel: "バリエーション",
+    watchFolderLabel: "監視フォルダ",
   },
   en: {
     alertOnLaunchModLoader: "The mod loader will be launched. Please ensure that 'Install client' is checked, then click 'Next'.",
     alertOnFailedAddProfile: "Failed to add profile. Please launch the Minecraft launcher and add it manually.",
     alertOnFailedLaunchModLoader: "Failed to launch mod loader. Please run the downloaded mod loader manually.",
+    alertOnFailedUpdateProfile: "Failed to update profile. Please change the profile settings manually in the Minecraft launcher.",
+    alertOnDuplicateMod: "The mods folder contains a duplicate of a modpack mod. Quarantine the added file if the game fails to start: ",
+    alertOnIncompatibleMods: "The following mods may not support the Minecraft or mod loader version of the profile: ",
     appTitle: "Makibania Modpack Installer",
+    back: "Back",
+    cachePeerLabel: "LAN mirror (e.g. 192.168.0.10:47625)",
+    cacheServerAddressLabel: "Share on address",
+    changeOptions: "Change options",
+    channelLabel: "Release channel",
+    clearCache: "Clear",
     close: "Close",
     complete: "Complete",
+    disabledMods: "Disabled mods: ",
+    downloadCache: "Download cache: ",
     error: "Error",
     install: "Install",
     installFailed: "Installation failed.\nDetails: ",
@@ -76,8 +135,13 @@ export const translations: Record<Language, Translation> = {
     languageOptionEn: "English",
     languageOptionJa: "Japanese",
     languageSelectionLabel: "Display language",
+    manualDownloadMessage: "The following files cannot be downloaded automatically. Download them from their pages and files in the watched folder will be picked up automatically.",
+    next: "Next",
     occurredError: "An error has occurred.\nDetails: ",
     openLogFolder: "Open log folder",
+    openProjectPage: "Open page",
+    optionsMessage: "Choose the optional content to install.",
+    packDownloaded: "Downloaded a new modpack version: ",
     phaseAddProfile: "Adding profile...",
     phaseDownloadModLoader: "Downloading mod loader...",
     phaseDownloadMods: "Downloading mods...",
@@ -88,8 +152,16 @@ export const translations: Record<Language, Translation> = {
     phaseRemoveMods: "Removing unnecessary mods...",
     phaseStart: "Starting installation...",
     phaseUpdateSettings: "Updating settings...",
+    pickFile: "Pick file",
+    quarantine: "Quarantine",
+    quarantineFailed: "Failed to quarantine the mod. Details: ",
+    shareCache: "Share on LAN",
+    sharingCache: "Sharing on LAN: ",
+    stopSharingCache: "Stop sharing",
     titleMessage: "Choose how you want to proceed.",
     update: "Update",
     updateFailed: "Update failed.\nDetails: ",
+    variantLabel: "Variant",
+    watchFolderLabel: "Watched folder",
   },
 };